@@ -0,0 +1,197 @@
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+
+use crate::error::{TypeCmdError, Result};
+use crate::tr;
+
+/// Default alias file name, stored next to the history file
+pub const ALIAS_FILE: &str = ".typecmd_aliases";
+
+/// User-defined command aliases
+#[derive(Debug, Clone, Default)]
+pub struct AliasStore {
+    aliases: BTreeMap<String, String>,
+    /// Aliases that last only for this session, such as those defined by
+    /// startup commands. They win over saved aliases and are never saved.
+    session: BTreeMap<String, String>,
+    /// File the aliases are loaded from, if any
+    path: Option<PathBuf>,
+    /// Whether changes are written back to the file
+    persist: bool,
+}
+
+impl AliasStore {
+    /// Create an alias store that is not persisted
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Create an alias store that loads existing aliases from `path`.
+    /// Changes are written back only when `persist` is set.
+    pub fn with_file(path: PathBuf, persist: bool) -> Result<Self> {
+        let mut store = AliasStore {
+            path: Some(path),
+            persist,
+            ..Self::default()
+        };
+        store.load()?;
+        Ok(store)
+    }
+    
+    /// Load aliases from file, skipping malformed lines
+    fn load(&mut self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if !path.exists() {
+            return Ok(());
+        }
+        
+        let reader = BufReader::new(File::open(path)?);
+        for line in reader.lines() {
+            let line = line?;
+            if let Some((name, expansion)) = line.split_once('=')
+                && Self::validate_name(name).is_ok()
+            {
+                self.aliases.insert(name.to_string(), expansion.to_string());
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// Save aliases to file
+    fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if !self.persist {
+            return Ok(());
+        }
+        
+        if self.aliases.is_empty() {
+            let _ = fs::remove_file(path);
+            return Ok(());
+        }
+        
+        let mut file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(path)?;
+        for (name, expansion) in &self.aliases {
+            writeln!(file, "{}={}", name, expansion)?;
+        }
+        
+        Ok(())
+    }
+    
+    /// Check that an alias name is a single plain word
+    pub fn validate_name(name: &str) -> Result<()> {
+        let invalid = name.is_empty()
+            || name.chars().any(|c| c.is_whitespace() || "=;&|$'\"\\".contains(c));
+        if invalid {
+            return Err(TypeCmdError::Parse(tr!("alias.invalid", name)));
+        }
+        Ok(())
+    }
+    
+    /// Get the expansion of an alias
+    pub fn get(&self, name: &str) -> Option<&String> {
+        self.session.get(name).or_else(|| self.aliases.get(name))
+    }
+    
+    /// Define or replace an alias and save it
+    pub fn set(&mut self, name: String, expansion: String) -> Result<()> {
+        Self::validate_name(&name)?;
+        self.session.remove(&name);
+        self.aliases.insert(name, expansion);
+        self.save()
+    }
+    
+    /// Define or replace an alias for this session only, without saving it
+    pub fn set_session(&mut self, name: String, expansion: String) -> Result<()> {
+        Self::validate_name(&name)?;
+        self.session.insert(name, expansion);
+        Ok(())
+    }
+    
+    /// Remove an alias, returning whether it existed
+    pub fn remove(&mut self, name: &str) -> Result<bool> {
+        let in_session = self.session.remove(name).is_some();
+        let saved = self.aliases.remove(name).is_some();
+        if saved {
+            self.save()?;
+        }
+        Ok(in_session || saved)
+    }
+    
+    /// Get all aliases sorted by name
+    pub fn all(&self) -> BTreeMap<&str, &str> {
+        self.aliases
+            .iter()
+            .chain(&self.session)
+            .map(|(name, expansion)| (name.as_str(), expansion.as_str()))
+            .collect()
+    }
+    
+    /// Get the number of aliases
+    pub fn len(&self) -> usize {
+        self.all().len()
+    }
+    
+    /// Check if there are no aliases
+    pub fn is_empty(&self) -> bool {
+        self.aliases.is_empty() && self.session.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alias_file(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("typecmd-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn session_aliases_are_not_saved() {
+        let path = alias_file("session_aliases");
+        let mut store = AliasStore::with_file(path.clone(), true).unwrap();
+        store.set("ll".to_string(), "history".to_string()).unwrap();
+        store.set_session("ll".to_string(), "history 5".to_string()).unwrap();
+        store.set_session("v".to_string(), "vars".to_string()).unwrap();
+        assert_eq!(store.get("ll").unwrap(), "history 5");
+        assert_eq!(store.len(), 2);
+
+        let reloaded = AliasStore::with_file(path.clone(), true).unwrap();
+        assert_eq!(reloaded.get("ll").unwrap(), "history");
+        assert!(reloaded.get("v").is_none());
+
+        assert!(store.remove("ll").unwrap());
+        assert!(store.get("ll").is_none());
+        assert!(AliasStore::with_file(path, true).unwrap().is_empty());
+    }
+
+    #[test]
+    fn saved_aliases_load_without_persisting() {
+        let path = alias_file("read_only_aliases");
+        AliasStore::with_file(path.clone(), true).unwrap()
+            .set("h".to_string(), "history".to_string()).unwrap();
+
+        let mut store = AliasStore::with_file(path.clone(), false).unwrap();
+        assert_eq!(store.get("h").unwrap(), "history");
+        store.set("v".to_string(), "vars".to_string()).unwrap();
+        assert!(store.remove("h").unwrap());
+
+        let reloaded = AliasStore::with_file(path, true).unwrap();
+        assert_eq!(reloaded.get("h").unwrap(), "history");
+        assert!(reloaded.get("v").is_none());
+    }
+}
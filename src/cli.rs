@@ -0,0 +1,96 @@
+//! Command line argument handling for the `typecmd` binary
+
+use std::env;
+use std::path::PathBuf;
+
+use crate::error::{TypeCmdError, Result};
+use crate::i18n::Lang;
+use crate::tr;
+
+/// Options parsed from the process arguments
+#[derive(Debug, Default)]
+pub struct CliOptions {
+    /// Commands given with `-c`
+    pub command: Option<String>,
+    /// Script file given as a positional argument
+    pub script: Option<PathBuf>,
+    pub keep_going: bool,
+    pub no_history: bool,
+    pub history_file: Option<PathBuf>,
+    /// Configuration file given with `--config`
+    pub config: Option<PathBuf>,
+    pub no_color: bool,
+    /// Message language given with `--lang`
+    pub lang: Option<Lang>,
+    pub quiet: bool,
+    pub show_help: bool,
+    pub show_version: bool,
+}
+
+impl CliOptions {
+    /// Parse options from the process arguments
+    pub fn from_env() -> Result<Self> {
+        Self::parse(env::args().skip(1))
+    }
+    
+    /// Parse options from an argument list, excluding the program name
+    pub fn parse<I>(args: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut options = CliOptions::default();
+        let mut args = args.into_iter();
+        
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-c" | "--command" => {
+                    options.command = Some(args.next().ok_or_else(|| {
+                        TypeCmdError::InsufficientArgs(tr!("cli.needs_command", arg))
+                    })?);
+                }
+                "--history-file" => {
+                    let path = args.next().ok_or_else(|| {
+                        TypeCmdError::InsufficientArgs(tr!("cli.needs_path", "--history-file"))
+                    })?;
+                    options.history_file = Some(PathBuf::from(path));
+                }
+                "--config" => {
+                    let path = args.next().ok_or_else(|| {
+                        TypeCmdError::InsufficientArgs(tr!("cli.needs_path", "--config"))
+                    })?;
+                    options.config = Some(PathBuf::from(path));
+                }
+                "--lang" => {
+                    let tag = args.next().ok_or_else(|| {
+                        TypeCmdError::InsufficientArgs(tr!("cli.needs_lang"))
+                    })?;
+                    options.lang = Some(Lang::parse(&tag).ok_or_else(|| {
+                        TypeCmdError::Parse(tr!("config.unknown_lang", tag))
+                    })?);
+                }
+                "-k" | "--keep-going" => options.keep_going = true,
+                "--no-history" => options.no_history = true,
+                "--no-color" => options.no_color = true,
+                "-q" | "--quiet" => options.quiet = true,
+                "-h" | "--help" => options.show_help = true,
+                "-V" | "--version" => options.show_version = true,
+                _ if arg.starts_with('-') && arg != "-" => {
+                    return Err(TypeCmdError::Parse(tr!("cli.unknown_option", arg)));
+                }
+                _ => {
+                    if options.script.is_some() {
+                        return Err(TypeCmdError::Parse(tr!("cli.extra_argument", arg)));
+                    }
+                    options.script = Some(PathBuf::from(arg));
+                }
+            }
+        }
+        
+        Ok(options)
+    }
+}
+
+/// Usage text printed by `--help`
+pub fn usage() -> String {
+    tr!("cli.usage", env!("CARGO_PKG_VERSION"))
+}
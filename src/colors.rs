@@ -1,5 +1,13 @@
 //! Colorized console output utilities
 
+use std::env;
+use std::io::{self, IsTerminal, Write};
+use std::sync::RwLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::theme::{Role, Theme};
+use crate::tr;
+
 /// ANSI color codes
 pub const RED: &str = "\x1b[31m";
 pub const GREEN: &str = "\x1b[32m";
@@ -11,42 +19,120 @@ pub const GRAY: &str = "\x1b[90m";
 pub const BOLD: &str = "\x1b[1m";
 pub const RESET: &str = "\x1b[0m";
 
+static COLOR_ENABLED: AtomicBool = AtomicBool::new(true);
+static QUIET: AtomicBool = AtomicBool::new(false);
+/// Current theme; `None` until one is set, meaning the default theme
+static THEME: RwLock<Option<Theme>> = RwLock::new(None);
+
+/// Enable or disable ANSI colors in all output
+pub fn set_color_enabled(enabled: bool) {
+    COLOR_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Check whether ANSI colors are enabled
+pub fn color_enabled() -> bool {
+    COLOR_ENABLED.load(Ordering::Relaxed)
+}
+
+/// Check whether stdout can show colors: it is a terminal and the
+/// `NO_COLOR` environment variable is not set
+pub fn terminal_supports_color() -> bool {
+    let no_color = env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    !no_color && io::stdout().is_terminal()
+}
+
+/// Set the theme used for all output
+pub fn set_theme(theme: Theme) {
+    *THEME.write().unwrap_or_else(|e| e.into_inner()) = Some(theme);
+}
+
+/// Current theme
+pub fn theme() -> Theme {
+    THEME.read().unwrap_or_else(|e| e.into_inner()).clone().unwrap_or_default()
+}
+
+/// Color text with the current theme's style for `role`
+pub fn paint(text: &str, role: Role) -> String {
+    let theme = theme();
+    let code = theme.style(role).code();
+    if code.is_empty() {
+        text.to_string()
+    } else {
+        colorize(text, code)
+    }
+}
+
+/// Suppress informational output; errors and warnings are still printed
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+}
+
+/// Check whether informational output is suppressed
+pub fn is_quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
 /// Colorize text with the given ANSI color code
 pub fn colorize(text: &str, color_code: &str) -> String {
-    format!("{}{}{}", color_code, text, RESET)
+    if color_enabled() {
+        format!("{}{}{}", color_code, text, RESET)
+    } else {
+        text.to_string()
+    }
+}
+
+/// Write `text` to stdout. Unlike `print!`, a closed pipe as in
+/// `typecmd -c 'show help' | head` doesn't panic; the text is dropped and
+/// the command's own exit status is kept.
+pub fn print_text(text: &str) {
+    let mut stdout = io::stdout().lock();
+    let _ = stdout.write_all(text.as_bytes()).and_then(|()| stdout.flush());
+}
+
+/// Write `text` and a newline to stdout, see `print_text`
+pub fn print_line(text: &str) {
+    print_text(&format!("{}\n", text));
 }
 
-/// Print error message in red
+/// Print error message in the theme's error style
 pub fn print_error(msg: &str) {
-    println!("{}错误: {}{}", RED, msg, RESET);
+    print_line(&paint(&tr!("prefix.error", msg), Role::Error));
 }
 
-/// Print success message in green
+/// Print success message in the theme's success style
 pub fn print_success(msg: &str) {
-    println!("{}{}{}", GREEN, msg, RESET);
+    if !is_quiet() {
+        print_line(&paint(msg, Role::Success));
+    }
 }
 
-/// Print info message in blue
+/// Print info message in the theme's info style
 pub fn print_info(msg: &str) {
-    println!("{}{}{}", BLUE, msg, RESET);
+    if !is_quiet() {
+        print_line(&paint(msg, Role::Info));
+    }
 }
 
-/// Print warning message in yellow
+/// Print warning message in the theme's warning style
 pub fn print_warn(msg: &str) {
-    println!("{}警告: {}{}", YELLOW, msg, RESET);
+    print_line(&paint(&tr!("prefix.warn", msg), Role::Warn));
 }
 
-/// Print message in gray
+/// Print message in the theme's muted style
 pub fn print_gray(msg: &str) {
-    println!("{}{}{}", GRAY, msg, RESET);
+    if !is_quiet() {
+        print_line(&paint(msg, Role::Muted));
+    }
 }
 
 /// Print message in cyan
 pub fn print_cyan(msg: &str) {
-    println!("{}{}{}", CYAN, msg, RESET);
+    if !is_quiet() {
+        print_line(&colorize(msg, CYAN));
+    }
 }
 
 /// Format text as bold
 pub fn bold(text: &str) -> String {
-    format!("{}{}{}", BOLD, text, RESET)
+    colorize(text, BOLD)
 }
\ No newline at end of file
@@ -1,30 +1,88 @@
+use crate::filter::Filter;
+use crate::value::Value;
+
 /// Command enum representing all possible commands
 #[derive(Debug)]
 pub enum Command {
     Show(ShowSubcommand),
     Exit(Option<i32>),
-    Set(String, String),
+    Set(String, Value),
+    /// `set name` at the end of a pipeline, storing the piped input
+    SetInput(String),
+    /// Define a variable in the innermost scope
+    Local(String, Value),
+    /// `readonly name [value]` and `const name value`
+    ReadOnly(String, Option<Value>),
+    /// Show or change attributes; each flag is an attribute name and
+    /// whether to turn it on
+    Attr(String, Vec<(String, bool)>),
+    SetExpr(String, String, NumericType),
     Get(String),
-    Copy(String, String),
-    ISet(String, i64),
+    /// `copy new old`; the flag is set when extra arguments were ignored
+    Copy(String, String, bool),
     StringCmd(String),
-    IntCmd(Option<i32>),
+    IntCmd(Option<String>),
+    Calc(String),
     List,
     Delete(String),
     Clear(ClearTarget),
     History(HistorySubcommand),
     LastCommand,
     HistoryCommand(String),
+    Source(String),
+    Alias(AliasSubcommand),
+    Config(String, String),
+    Theme(Option<String>),
+    /// `save vars [file]`, `None` uses the default variable file
+    SaveVars(Option<String>),
+    /// `load vars [file]`, `None` uses the default variable file
+    LoadVars(Option<String>),
+    /// `export json [file]`, `None` prints the document
+    ExportJson(Option<String>),
+    /// `import json <file>`, replacing all variables when the flag is set
+    ImportJson(String, bool),
+    /// `env [name]`, list the environment or show one variable
+    Env(Option<String>),
+    /// `import env`, `None` imports the whole environment
+    ImportEnv(Option<Vec<String>>),
+    /// Mark variables as exported to spawned processes
+    Export(Vec<String>),
+    /// Run an external program with arguments
+    Run(Vec<String>),
+    /// Transform piped input
+    Filter(Filter),
+}
+
+/// Connector joining a command to the previous one in a chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Connector {
+    /// `;` - always run
+    Always,
+    /// `&&` - run if the previous command succeeded
+    And,
+    /// `||` - run if the previous command failed
+    Or,
+}
+
+/// Target type of an evaluated numeric assignment
+#[derive(Debug, Clone, Copy)]
+pub enum NumericType {
+    Int,
+    Float,
 }
 
 /// Show command subcommands
 #[derive(Debug)]
 pub enum ShowSubcommand {
-    Help,
+    Help(Option<String>),
     Version,
     Variables,
+    /// `show vars --scope`, variables grouped by scope
+    Scopes,
     History(Option<usize>),
     License, 
+    Aliases,
+    Config,
 }
 
 /// Clear command targets
@@ -34,6 +92,15 @@ pub enum ClearTarget {
     History,
 }
 
+/// Alias command subcommands
+#[derive(Debug)]
+pub enum AliasSubcommand {
+    List,
+    Show(String),
+    Define(String, String),
+    Remove(String),
+}
+
 /// History command subcommands
 #[derive(Debug)]
 pub enum HistorySubcommand {
@@ -0,0 +1,108 @@
+//! Context-aware tab completion for the interactive prompt
+
+use crate::editor::Completer;
+use crate::history::HistoryManager;
+use crate::registry::{command_names, find_command, ArgKind};
+use crate::variables::VariableStore;
+
+/// Completes command names, subcommands, variable names and history ids
+pub struct CommandCompleter<'a> {
+    variables: &'a VariableStore,
+    history: &'a HistoryManager,
+    extra_commands: Vec<String>,
+    aliases: Vec<String>,
+}
+
+impl<'a> CommandCompleter<'a> {
+    /// Create a completer over the given variables and history
+    pub fn new(variables: &'a VariableStore, history: &'a HistoryManager) -> Self {
+        CommandCompleter { variables, history, extra_commands: Vec::new(), aliases: Vec::new() }
+    }
+
+    /// Also complete these command names, e.g. registered custom commands
+    pub fn with_commands(mut self, names: Vec<String>) -> Self {
+        self.extra_commands = names;
+        self
+    }
+
+    /// Also complete these user-defined alias names
+    pub fn with_aliases(mut self, names: Vec<String>) -> Self {
+        self.aliases = names;
+        self
+    }
+
+    fn all_command_names(&self) -> Vec<String> {
+        command_names()
+            .map(|name| name.to_string())
+            .chain(self.extra_commands.iter().cloned())
+            .chain(self.aliases.iter().cloned())
+            .collect()
+    }
+
+    fn variable_names(&self) -> Vec<String> {
+        self.variables.listed().into_iter().map(|(name, _, _)| name.clone()).collect()
+    }
+
+    fn history_ids(&self) -> Vec<String> {
+        self.history.get(None).iter().rev().map(|entry| entry.id.to_string()).collect()
+    }
+
+    /// Candidates for the next word of a command whose previous words
+    /// are `words`
+    fn candidates(&self, words: &[&str]) -> Vec<String> {
+        let Some(first) = words.first() else {
+            return self.all_command_names();
+        };
+        let Some(spec) = find_command(first) else {
+            return Vec::new();
+        };
+
+        match spec.arg_kind(words.len() - 1) {
+            ArgKind::Variable => self.variable_names(),
+            ArgKind::Values(values) => values.iter().map(|value| value.to_string()).collect(),
+            ArgKind::HistoryId => self.history_ids(),
+            ArgKind::Alias => self.aliases.clone(),
+            ArgKind::Command => self.all_command_names(),
+            ArgKind::Text => Vec::new(),
+        }
+    }
+}
+
+impl Completer for CommandCompleter<'_> {
+    fn complete(&self, line: &str) -> (usize, Vec<String>) {
+        // Only the last command of a chain or pipeline matters
+        let command_start = ["&&", "||", ";", "|"]
+            .iter()
+            .filter_map(|sep| line.rfind(sep).map(|i| i + sep.len()))
+            .max()
+            .unwrap_or(0);
+        let command = &line[command_start..];
+
+        let word_start = command
+            .rfind(char::is_whitespace)
+            .map(|i| i + 1)
+            .unwrap_or(0);
+        let word = &command[word_start..];
+        let start = line[..command_start + word_start].chars().count();
+
+        // `$name` references complete to variable names anywhere
+        if let Some(prefix) = word.strip_prefix('$') {
+            let candidates = self
+                .variable_names()
+                .into_iter()
+                .filter(|name| name.starts_with(prefix))
+                .map(|name| format!("${}", name))
+                .collect();
+            return (start, candidates);
+        }
+
+        let words: Vec<&str> = command[..word_start].split_whitespace().collect();
+        let mut candidates: Vec<String> = self
+            .candidates(&words)
+            .into_iter()
+            .filter(|candidate| candidate.starts_with(word))
+            .collect();
+        candidates.dedup();
+        (start, candidates)
+    }
+}
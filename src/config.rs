@@ -0,0 +1,221 @@
+//! Startup configuration file (`~/.typecmdrc`)
+//!
+//! The file uses the TypeCmd script syntax. `config <key> <value>` lines
+//! change settings that have to be known before the session starts, such
+//! as the history file; every other line is run as a command once the
+//! session is set up, so `set` and `alias` lines define the initial
+//! variables and aliases.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::{TypeCmdError, Result};
+use crate::history::HistoryConfig;
+use crate::i18n::Lang;
+use crate::parser::parse_command;
+use crate::theme::{Role, Style, Theme};
+use crate::tr;
+use crate::value::Value;
+
+/// Default configuration file name in the home directory
+pub const CONFIG_FILE: &str = ".typecmdrc";
+
+/// Keys accepted by `config <key> <value>`
+pub const CONFIG_KEYS: &[&str] = &[
+    "history.size", "history.file", "history.persist",
+    "prompt", "prompt.right", "prompt.continuation", "color", "theme",
+    "theme.error", "theme.success", "theme.info", "theme.warn", "theme.muted", "theme.prompt",
+    "lang", "undefined_empty", "vars.autosave", "run.fallback",
+];
+
+/// Effective session configuration
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub history: HistoryConfig,
+    /// Prompt template replacing the built-in prompt, see `crate::prompt`
+    pub prompt: Option<String>,
+    /// Template shown at the right edge of the input line
+    pub right_prompt: Option<String>,
+    /// Template shown before continuation lines of multi-line input
+    pub continuation_prompt: String,
+    /// Use colors when the terminal supports them
+    pub color: bool,
+    pub theme: Theme,
+    /// Message language; `None` follows the environment
+    pub lang: Option<Lang>,
+    /// Expand undefined `$name` references to an empty string
+    pub undefined_as_empty: bool,
+    /// Save variables to the variable file whenever they change, and load
+    /// them at startup
+    pub autosave_vars: bool,
+    /// Run unknown commands as external programs found in `PATH`
+    pub run_fallback: bool,
+    /// File the configuration was loaded from
+    pub path: Option<PathBuf>,
+    /// Commands to run at startup. `config` lines are blanked out so line
+    /// numbers still match the file.
+    pub startup: String,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            history: HistoryConfig::default(),
+            prompt: None,
+            right_prompt: None,
+            continuation_prompt: "> ".to_string(),
+            color: true,
+            theme: Theme::default(),
+            lang: None,
+            undefined_as_empty: false,
+            autosave_vars: false,
+            run_fallback: false,
+            path: None,
+            startup: String::new(),
+        }
+    }
+}
+
+impl Config {
+    /// Path of the default configuration file in the home directory
+    pub fn default_path() -> PathBuf {
+        let home = env::var("HOME").unwrap_or_else(|_| ".".to_string());
+        PathBuf::from(home).join(CONFIG_FILE)
+    }
+
+    /// Load the default configuration file, falling back to the defaults
+    /// when it does not exist
+    pub fn load_default() -> Result<Self> {
+        let path = Self::default_path();
+        if path.exists() {
+            Self::load(&path)
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    /// Load a configuration file
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)?;
+        let mut config = Self::parse(&path.display().to_string(), &content)?;
+        config.path = Some(path.to_path_buf());
+        Ok(config)
+    }
+
+    /// Parse configuration text. `file` is only used in error messages.
+    pub fn parse(file: &str, content: &str) -> Result<Self> {
+        let mut config = Self::default();
+
+        for (index, line) in content.lines().enumerate() {
+            let trimmed = line.trim();
+            let is_setting = trimmed
+                .split_whitespace()
+                .next()
+                .is_some_and(|word| word.eq_ignore_ascii_case("config"));
+
+            if is_setting {
+                config.apply_line(trimmed).map_err(|e| TypeCmdError::Script {
+                    file: file.to_string(),
+                    line: index + 1,
+                    source: Box::new(e),
+                })?;
+            } else {
+                config.startup.push_str(line);
+            }
+            config.startup.push('\n');
+        }
+
+        Ok(config)
+    }
+
+    fn apply_line(&mut self, line: &str) -> Result<()> {
+        let tokens = parse_command(line)?;
+        if tokens.len() < 3 {
+            return Err(TypeCmdError::InsufficientArgs(
+                tr!("args.config_value"),
+            ));
+        }
+        self.set(&tokens[1], &tokens[2..].join(" "))
+    }
+
+    /// Change a setting by key
+    pub fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        match key.to_lowercase().as_str() {
+            "history.size" => {
+                self.history.max_history_size = value.parse().map_err(|_| {
+                    TypeCmdError::Parse(tr!("config.history_size", value))
+                })?;
+            }
+            "history.file" => self.history.history_file = value.to_string(),
+            "history.persist" => self.history.persist = parse_flag(value)?,
+            "prompt" => self.prompt = optional_template(value),
+            "prompt.right" => self.right_prompt = optional_template(value),
+            "prompt.continuation" => self.continuation_prompt = value.to_string(),
+            "color" => self.color = parse_flag(value)?,
+            "theme" => {
+                self.theme = Theme::builtin(&value.to_lowercase()).ok_or_else(|| {
+                    TypeCmdError::Parse(tr!("config.unknown_theme", value))
+                })?;
+            }
+            "lang" => {
+                self.lang = match value {
+                    "" | "default" => None,
+                    tag => Some(Lang::parse(tag).ok_or_else(|| {
+                        TypeCmdError::Parse(tr!("config.unknown_lang", tag))
+                    })?),
+                };
+            }
+            other if other.starts_with("theme.") => {
+                let role = Role::from_name(&other["theme.".len()..])
+                    .ok_or_else(|| TypeCmdError::Parse(tr!("config.unknown_key", key)))?;
+                self.theme.set_style(role, Style::parse(value)?);
+            }
+            "undefined_empty" => self.undefined_as_empty = parse_flag(value)?,
+            "vars.autosave" => self.autosave_vars = parse_flag(value)?,
+            "run.fallback" => self.run_fallback = parse_flag(value)?,
+            _ => return Err(TypeCmdError::Parse(tr!("config.unknown_key", key))),
+        }
+        Ok(())
+    }
+
+    /// All settings as `(key, value)` pairs, in `CONFIG_KEYS` order
+    pub fn entries(&self) -> Vec<(String, String)> {
+        let mut entries = vec![
+            ("history.size".to_string(), self.history.max_history_size.to_string()),
+            ("history.file".to_string(), self.history.history_file.clone()),
+            ("history.persist".to_string(), self.history.persist.to_string()),
+            ("prompt".to_string(), self.prompt.clone().unwrap_or_else(|| "default".to_string())),
+            ("prompt.right".to_string(), self.right_prompt.clone().unwrap_or_else(|| "default".to_string())),
+            ("prompt.continuation".to_string(), self.continuation_prompt.clone()),
+            ("color".to_string(), self.color.to_string()),
+            ("theme".to_string(), self.theme.name.clone()),
+        ];
+        for role in Role::ALL {
+            entries.push((format!("theme.{}", role.name()), self.theme.style(role).to_string()));
+        }
+        let lang = self.lang.map_or("default", Lang::tag);
+        entries.push(("lang".to_string(), lang.to_string()));
+        entries.push(("undefined_empty".to_string(), self.undefined_as_empty.to_string()));
+        entries.push(("vars.autosave".to_string(), self.autosave_vars.to_string()));
+        entries.push(("run.fallback".to_string(), self.run_fallback.to_string()));
+        entries
+    }
+}
+
+/// `default` or an empty value restores the built-in behavior
+fn optional_template(value: &str) -> Option<String> {
+    match value {
+        "" | "default" => None,
+        text => Some(text.to_string()),
+    }
+}
+
+fn parse_flag(value: &str) -> Result<bool> {
+    match value.to_lowercase().as_str() {
+        "on" | "yes" | "1" => Ok(true),
+        "off" | "no" | "0" => Ok(false),
+        _ => Value::parse_bool(value)
+            .ok_or_else(|| TypeCmdError::Parse(tr!("config.bool", value))),
+    }
+}
@@ -0,0 +1,591 @@
+//! Interactive line editor for the REPL prompt
+//!
+//! Supports cursor movement, Up/Down history traversal, Ctrl-R incremental
+//! reverse search, Tab completion and kill/yank shortcuts. The terminal is switched to raw
+//! mode only while a line is being read.
+
+use std::io::{self, IsTerminal, Read, Write};
+use std::time::Duration;
+
+use crate::history::HistoryManager;
+
+/// One line of interactive input
+pub enum LineInput {
+    Line(String),
+    /// Ctrl-C cancelled the line; `empty` tells whether nothing was typed
+    Interrupted { empty: bool },
+    Eof,
+}
+
+/// Source of Tab completion candidates
+pub trait Completer {
+    /// Complete the word that ends at the end of `line`, returning the
+    /// char index where that word starts and the candidate replacements
+    fn complete(&self, line: &str) -> (usize, Vec<String>);
+}
+
+/// Decoded key press
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Key {
+    Char(char),
+    Ctrl(char),
+    Enter,
+    Backspace,
+    Delete,
+    Left,
+    Right,
+    Up,
+    Down,
+    Home,
+    End,
+    Escape,
+    Unknown,
+}
+
+/// Line editor state kept between lines
+#[derive(Debug, Default)]
+pub struct LineEditor {
+    kill_buffer: String,
+}
+
+/// State of the line currently being edited
+struct EditState<'a> {
+    prompt: &'a str,
+    /// Text shown right-aligned on the input line while it fits
+    right_prompt: &'a str,
+    buffer: Vec<char>,
+    cursor: usize,
+    /// Index into the history entries while browsing with Up/Down
+    history_index: Option<usize>,
+    /// Line being typed before history browsing started
+    draft: Vec<char>,
+}
+
+impl LineEditor {
+    /// Create a new line editor
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check whether interactive editing is possible, i.e. stdin is a
+    /// terminal on a supported platform
+    pub fn is_supported() -> bool {
+        cfg!(unix) && io::stdin().is_terminal()
+    }
+
+    /// Read a line, showing `prompt` before the input.
+    ///
+    /// `prompt` must fit on a single line; it is redrawn on every edit.
+    pub fn read_line(
+        &mut self,
+        prompt: &str,
+        history: &HistoryManager,
+        completer: &dyn Completer,
+    ) -> io::Result<LineInput> {
+        self.read_line_with_right(prompt, "", history, completer)
+    }
+
+    /// Read a line like `read_line`, also showing `right_prompt` at the
+    /// right edge of the terminal. The right prompt is hidden while the
+    /// input would run into it.
+    pub fn read_line_with_right(
+        &mut self,
+        prompt: &str,
+        right_prompt: &str,
+        history: &HistoryManager,
+        completer: &dyn Completer,
+    ) -> io::Result<LineInput> {
+        let _raw = RawMode::enable()?;
+        let mut input = Terminal;
+        let mut state = EditState {
+            prompt,
+            right_prompt,
+            buffer: Vec::new(),
+            cursor: 0,
+            history_index: None,
+            draft: Vec::new(),
+        };
+        state.redraw()?;
+        let mut last_key = Key::Unknown;
+
+        loop {
+            let key = read_key(&mut input)?;
+            match key {
+                Key::Ctrl('i') => {
+                    // A second Tab without progress lists the candidates
+                    state.complete(completer, last_key == Key::Ctrl('i'))?;
+                }
+                Key::Enter => {
+                    print!("\r\n");
+                    io::stdout().flush()?;
+                    return Ok(LineInput::Line(state.buffer.iter().collect()));
+                }
+                Key::Ctrl('c') => {
+                    print!("^C\r\n");
+                    io::stdout().flush()?;
+                    return Ok(LineInput::Interrupted { empty: state.buffer.is_empty() });
+                }
+                Key::Ctrl('d') if state.buffer.is_empty() => {
+                    print!("\r\n");
+                    io::stdout().flush()?;
+                    return Ok(LineInput::Eof);
+                }
+                Key::Ctrl('r') => {
+                    if let Some(line) = self.reverse_search(&mut input, &mut state, history)? {
+                        print!("\r\n");
+                        io::stdout().flush()?;
+                        return Ok(LineInput::Line(line));
+                    }
+                }
+                _ => self.handle_key(key, &mut state, history),
+            }
+            last_key = key;
+            state.redraw()?;
+        }
+    }
+
+    /// Apply an editing key to the current line
+    fn handle_key(&mut self, key: Key, state: &mut EditState, history: &HistoryManager) {
+        match key {
+            Key::Char(c) => {
+                state.buffer.insert(state.cursor, c);
+                state.cursor += 1;
+            }
+            Key::Backspace | Key::Ctrl('h') if state.cursor > 0 => {
+                state.cursor -= 1;
+                state.buffer.remove(state.cursor);
+            }
+            Key::Delete | Key::Ctrl('d') if state.cursor < state.buffer.len() => {
+                state.buffer.remove(state.cursor);
+            }
+            Key::Left | Key::Ctrl('b') => state.cursor = state.cursor.saturating_sub(1),
+            Key::Right | Key::Ctrl('f') => state.cursor = (state.cursor + 1).min(state.buffer.len()),
+            Key::Home | Key::Ctrl('a') => state.cursor = 0,
+            Key::End | Key::Ctrl('e') => state.cursor = state.buffer.len(),
+            Key::Up | Key::Ctrl('p') => state.history_prev(history),
+            Key::Down | Key::Ctrl('n') => state.history_next(history),
+            Key::Ctrl('k') => {
+                self.kill_buffer = state.buffer.drain(state.cursor..).collect();
+            }
+            Key::Ctrl('u') => {
+                self.kill_buffer = state.buffer.drain(..state.cursor).collect();
+                state.cursor = 0;
+            }
+            Key::Ctrl('w') => {
+                let end = state.cursor;
+                let mut start = end;
+                while start > 0 && state.buffer[start - 1] == ' ' {
+                    start -= 1;
+                }
+                while start > 0 && state.buffer[start - 1] != ' ' {
+                    start -= 1;
+                }
+                self.kill_buffer = state.buffer.drain(start..end).collect();
+                state.cursor = start;
+            }
+            Key::Ctrl('y') => {
+                for c in self.kill_buffer.chars() {
+                    state.buffer.insert(state.cursor, c);
+                    state.cursor += 1;
+                }
+            }
+            Key::Ctrl('l') => {
+                print!("\x1b[2J\x1b[H");
+            }
+            _ => {}
+        }
+    }
+
+    /// Run Ctrl-R incremental reverse search.
+    ///
+    /// Returns `Some(line)` when the match is accepted with Enter. Any other
+    /// key leaves search mode with the match placed in the edit buffer.
+    fn reverse_search(
+        &mut self,
+        input: &mut Terminal,
+        state: &mut EditState,
+        history: &HistoryManager,
+    ) -> io::Result<Option<String>> {
+        let original = state.buffer.clone();
+        let mut query = String::new();
+        // Number of newer matches to skip, increased by repeated Ctrl-R
+        let mut skip = 0;
+
+        loop {
+            let found = history
+                .search(&query)
+                .into_iter()
+                .rev()
+                .nth(skip)
+                .map(|entry| entry.command.clone());
+            let shown = found.clone().unwrap_or_default();
+            let label = if found.is_some() || query.is_empty() {
+                "reverse-i-search"
+            } else {
+                "failed reverse-i-search"
+            };
+            print!("\r({})`{}': {}\x1b[K", label, query, shown);
+            io::stdout().flush()?;
+
+            match read_key(input)? {
+                Key::Char(c) => {
+                    query.push(c);
+                    skip = 0;
+                }
+                Key::Backspace | Key::Ctrl('h') => {
+                    query.pop();
+                    skip = 0;
+                }
+                Key::Ctrl('r') => {
+                    if found.is_some() {
+                        skip += 1;
+                    }
+                }
+                Key::Enter => return Ok(Some(shown)),
+                Key::Ctrl('g') | Key::Ctrl('c') | Key::Escape => {
+                    state.set_buffer(original);
+                    return Ok(None);
+                }
+                _ => {
+                    state.set_buffer(shown.chars().collect());
+                    return Ok(None);
+                }
+            }
+        }
+    }
+}
+
+impl EditState<'_> {
+    /// Replace the buffer and move the cursor to its end
+    fn set_buffer(&mut self, buffer: Vec<char>) {
+        self.cursor = buffer.len();
+        self.buffer = buffer;
+    }
+
+    /// Complete the word before the cursor
+    fn complete(&mut self, completer: &dyn Completer, list: bool) -> io::Result<()> {
+        let line: String = self.buffer[..self.cursor].iter().collect();
+        let (start, candidates) = completer.complete(&line);
+        let word_len = self.cursor - start;
+
+        let replacement = match candidates.as_slice() {
+            [] => return Ok(()),
+            [only] => format!("{} ", only),
+            _ => {
+                let common = common_prefix(&candidates);
+                if common.chars().count() > word_len {
+                    common
+                } else {
+                    if list {
+                        print!("\r\n{}\r\n", candidates.join("  "));
+                    }
+                    return Ok(());
+                }
+            }
+        };
+
+        let tail = self.buffer.split_off(self.cursor);
+        self.buffer.truncate(start);
+        self.buffer.extend(replacement.chars());
+        self.cursor = self.buffer.len();
+        self.buffer.extend(tail);
+        Ok(())
+    }
+
+    fn history_prev(&mut self, history: &HistoryManager) {
+        let entries = history.get(None);
+        let index = match self.history_index {
+            Some(0) => return,
+            Some(i) => i - 1,
+            None if entries.is_empty() => return,
+            None => {
+                self.draft = self.buffer.clone();
+                entries.len() - 1
+            }
+        };
+        self.history_index = Some(index);
+        self.set_buffer(entries[index].command.chars().collect());
+    }
+
+    fn history_next(&mut self, history: &HistoryManager) {
+        let Some(index) = self.history_index else {
+            return;
+        };
+        let entries = history.get(None);
+        if index + 1 < entries.len() {
+            self.history_index = Some(index + 1);
+            self.set_buffer(entries[index + 1].command.chars().collect());
+        } else {
+            self.history_index = None;
+            let draft = std::mem::take(&mut self.draft);
+            self.set_buffer(draft);
+        }
+    }
+
+    /// Redraw the prompt and buffer, then place the cursor
+    fn redraw(&self) -> io::Result<()> {
+        let line: String = self.buffer.iter().collect();
+        let mut out = format!("\r{}{}\x1b[K", self.prompt, line);
+        let tail_width: usize = self.buffer[self.cursor..].iter().map(|c| char_width(*c)).sum();
+
+        let right_width = display_width(self.right_prompt);
+        let used = display_width(self.prompt) + display_width(&line);
+        let columns = terminal_columns();
+        if right_width > 0 && used + right_width < columns {
+            // Draw at the right edge, then jump back to the cursor column
+            out.push_str(&format!("\x1b[{}G{}", columns - right_width + 1, self.right_prompt));
+            out.push_str(&format!("\x1b[{}G", used - tail_width + 1));
+        } else if tail_width > 0 {
+            out.push_str(&format!("\x1b[{}D", tail_width));
+        }
+        let mut stdout = io::stdout().lock();
+        stdout.write_all(out.as_bytes())?;
+        stdout.flush()
+    }
+}
+
+/// Longest common prefix of all candidates
+fn common_prefix(candidates: &[String]) -> String {
+    let mut prefix: Vec<char> = candidates[0].chars().collect();
+    for candidate in &candidates[1..] {
+        let len = prefix
+            .iter()
+            .zip(candidate.chars())
+            .take_while(|(a, b)| **a == *b)
+            .count();
+        prefix.truncate(len);
+    }
+    prefix.into_iter().collect()
+}
+
+/// Display width of text in a terminal cell grid, ignoring escape
+/// sequences such as colors
+pub(crate) fn display_width(text: &str) -> usize {
+    let mut width = 0;
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // Skip a CSI sequence up to its final letter
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            width += char_width(c);
+        }
+    }
+    width
+}
+
+/// Width of the terminal attached to stdout, 80 if unknown
+fn terminal_columns() -> usize {
+    #[cfg(unix)]
+    // SAFETY: winsize is a plain C struct that TIOCGWINSZ fills in.
+    unsafe {
+        let mut size: libc::winsize = std::mem::zeroed();
+        if libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) == 0 && size.ws_col > 0 {
+            return size.ws_col as usize;
+        }
+    }
+    80
+}
+
+/// Display width of a character in a terminal cell grid
+fn char_width(c: char) -> usize {
+    match c as u32 {
+        0x1100..=0x115F
+        | 0x2E80..=0xA4CF
+        | 0xAC00..=0xD7A3
+        | 0xF900..=0xFAFF
+        | 0xFE30..=0xFE4F
+        | 0xFF00..=0xFF60
+        | 0xFFE0..=0xFFE6
+        | 0x20000..=0x3FFFD => 2,
+        _ => 1,
+    }
+}
+
+fn read_byte<R: Read>(input: &mut R) -> io::Result<u8> {
+    let mut byte = [0u8; 1];
+    loop {
+        match input.read(&mut byte) {
+            Ok(0) => return Err(io::Error::from(io::ErrorKind::UnexpectedEof)),
+            Ok(_) => return Ok(byte[0]),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// How long to wait for the rest of an escape sequence before treating ESC
+/// as the Esc key
+const ESCAPE_TIMEOUT: Duration = Duration::from_millis(50);
+
+/// Unbuffered terminal input, so that pending bytes can be checked on the
+/// file descriptor
+struct Terminal;
+
+impl Terminal {
+    /// Wait up to `timeout` for input, returning whether a byte is ready
+    #[cfg(unix)]
+    fn ready(&self, timeout: Duration) -> io::Result<bool> {
+        let mut fd = libc::pollfd { fd: libc::STDIN_FILENO, events: libc::POLLIN, revents: 0 };
+        let millis = timeout.as_millis().try_into().unwrap_or(libc::c_int::MAX);
+        loop {
+            // SAFETY: polls a single valid pollfd for the stdin descriptor.
+            match unsafe { libc::poll(&mut fd, 1, millis) } {
+                -1 if io::Error::last_os_error().kind() == io::ErrorKind::Interrupted => continue,
+                -1 => return Err(io::Error::last_os_error()),
+                n => return Ok(n > 0),
+            }
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn ready(&self, _timeout: Duration) -> io::Result<bool> {
+        Ok(true)
+    }
+}
+
+impl Read for Terminal {
+    #[cfg(unix)]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        // SAFETY: reads into the given buffer, at most its length.
+        let n = unsafe { libc::read(libc::STDIN_FILENO, buf.as_mut_ptr().cast(), buf.len()) };
+        if n < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(n as usize)
+    }
+
+    #[cfg(not(unix))]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        io::stdin().read(buf)
+    }
+}
+
+/// Read and decode one key press
+fn read_key(input: &mut Terminal) -> io::Result<Key> {
+    let byte = match read_byte(input) {
+        Ok(byte) => byte,
+        // Treat a closed terminal like Ctrl-D on an empty line
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(Key::Ctrl('d')),
+        Err(e) => return Err(e),
+    };
+
+    Ok(match byte {
+        b'\r' | b'\n' => Key::Enter,
+        0x7f | 0x08 => Key::Backspace,
+        0x1b => read_escape(input)?,
+        0x01..=0x1a => Key::Ctrl((b'a' + byte - 1) as char),
+        0x00..=0x1f => Key::Unknown,
+        _ => read_utf8(input, byte)?,
+    })
+}
+
+/// Decode an escape sequence after the initial ESC byte. A lone ESC is
+/// the Esc key.
+///
+/// CSI sequences (`ESC [`) are read up to their final byte in
+/// `0x40..=0x7e`; modifiers such as Ctrl in `ESC [1;5D` are ignored.
+fn read_escape(input: &mut Terminal) -> io::Result<Key> {
+    if !input.ready(ESCAPE_TIMEOUT)? {
+        return Ok(Key::Escape);
+    }
+    let kind = read_byte(input)?;
+    let (params, code) = match kind {
+        b'O' => (Vec::new(), read_byte(input)?),
+        b'[' => {
+            let mut params = Vec::new();
+            loop {
+                match read_byte(input)? {
+                    code @ 0x40..=0x7e => break (params, code),
+                    b => params.push(b),
+                }
+            }
+        }
+        _ => return Ok(Key::Escape),
+    };
+
+    Ok(match code {
+        b'A' => Key::Up,
+        b'B' => Key::Down,
+        b'C' => Key::Right,
+        b'D' => Key::Left,
+        b'H' => Key::Home,
+        b'F' => Key::End,
+        b'~' => {
+            let number = params.split(|&b| b == b';').next().unwrap_or_default();
+            match number {
+                b"1" | b"7" => Key::Home,
+                b"4" | b"8" => Key::End,
+                b"3" => Key::Delete,
+                _ => Key::Unknown,
+            }
+        }
+        _ => Key::Unknown,
+    })
+}
+
+/// Decode a UTF-8 character starting with `first`
+fn read_utf8<R: Read>(input: &mut R, first: u8) -> io::Result<Key> {
+    let len = match first {
+        0xc0..=0xdf => 2,
+        0xe0..=0xef => 3,
+        0xf0..=0xf7 => 4,
+        _ => 1,
+    };
+    let mut bytes = vec![first];
+    for _ in 1..len {
+        bytes.push(read_byte(input)?);
+    }
+    Ok(match std::str::from_utf8(&bytes).ok().and_then(|s| s.chars().next()) {
+        Some(c) => Key::Char(c),
+        None => Key::Unknown,
+    })
+}
+
+/// Guard that keeps the terminal in raw mode until dropped
+struct RawMode {
+    #[cfg(unix)]
+    original: libc::termios,
+}
+
+impl RawMode {
+    #[cfg(unix)]
+    fn enable() -> io::Result<Self> {
+        // SAFETY: termios is a plain C struct filled in by tcgetattr before
+        // use, and only the stdin file descriptor is touched.
+        unsafe {
+            let mut original: libc::termios = std::mem::zeroed();
+            if libc::tcgetattr(libc::STDIN_FILENO, &mut original) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let mut raw = original;
+            raw.c_lflag &= !(libc::ICANON | libc::ECHO | libc::ISIG | libc::IEXTEN);
+            raw.c_iflag &= !(libc::IXON | libc::ICRNL);
+            raw.c_cc[libc::VMIN] = 1;
+            raw.c_cc[libc::VTIME] = 0;
+            if libc::tcsetattr(libc::STDIN_FILENO, libc::TCSADRAIN, &raw) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(RawMode { original })
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn enable() -> io::Result<Self> {
+        Err(io::Error::new(io::ErrorKind::Unsupported, "raw mode is not supported"))
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        // SAFETY: restores the attributes saved by `enable`.
+        unsafe {
+            libc::tcsetattr(libc::STDIN_FILENO, libc::TCSADRAIN, &self.original);
+        }
+    }
+}
@@ -1,28 +1,58 @@
 use std::io;
 use thiserror::Error;
 
-/// TypeCmd error types
+use crate::tr;
+
+/// TypeCmd error types, displayed in the current message language
 #[derive(Error, Debug)]
 pub enum TypeCmdError {
-    #[error("I/O错误: {0}")]
+    #[error("{}", tr!("error.io", .0))]
     Io(#[from] io::Error),
     
-    #[error("解析错误: {0}")]
+    #[error("{}", tr!("error.parse", .0))]
     Parse(String),
     
-    #[error("未找到命令: {0}")]
+    #[error("{}", tr!("error.command_not_found", .0))]
     CommandNotFound(String),
     
-    #[error("参数不足: {0}")]
+    #[error("{}", tr!("error.command_exists", .0))]
+    CommandExists(String),
+    
+    #[error("{}", tr!("error.insufficient_args", .0))]
     InsufficientArgs(String),
     
-    #[error("变量未定义: {0}")]
+    #[error("{}", tr!("error.undefined_variable", .0))]
     UndefinedVariable(String),
     
-    #[error("无效的历史命令: {0}")]
+    #[error("{}", tr!("error.invalid_history_command", .0))]
     InvalidHistoryCommand(String),
     
-    #[error("其他错误: {0}")]
+    #[error("{}", tr!("error.division_by_zero"))]
+    DivisionByZero,
+    
+    #[error("{}", tr!("error.overflow", .0))]
+    Overflow(String),
+    
+    #[error("{}", tr!("error.type_mismatch", .0))]
+    TypeMismatch(String),
+    
+    #[error("{}", tr!("error.readonly", .0))]
+    ReadOnly(String),
+    
+    #[error("{file}: {}", tr!("error.io", .source))]
+    File {
+        file: String,
+        source: io::Error,
+    },
+    
+    #[error("{file}:{line}: {source}")]
+    Script {
+        file: String,
+        line: usize,
+        source: Box<TypeCmdError>,
+    },
+    
+    #[error("{}", tr!("error.other", .0))]
     Other(String),
 }
 
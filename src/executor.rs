@@ -1,133 +1,860 @@
-use std::io::{self, Write};
-use std::process::exit;
+use std::env;
+use std::fs;
+use std::io::{self, BufRead, ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{self, ExitStatus, Stdio};
+use std::rc::Rc;
+use std::thread;
 use crate::error::{TypeCmdError, Result};
-use crate::colors::{print_error, print_success, print_info, print_warn, print_gray, bold, PURPLE, CYAN, GREEN, RESET};
-use crate::history::HistoryManager;
-use crate::variables::VariableStore;
-use crate::variablesint::VariableStoreInt;
-use crate::parser::{parse_command, parse_to_command};
-use crate::command::{Command, ShowSubcommand, ClearTarget, HistorySubcommand};
-use crate::colors::BLUE;
+use crate::i18n::{self, set_lang, Lang};
+use crate::tr;
+use crate::colors::{bold, paint, print_line, print_text, set_color_enabled, set_theme, terminal_supports_color, theme};
+use crate::theme::{Role, THEME_NAMES};
+use crate::config::{Config, CONFIG_FILE};
+use crate::history::{HistoryConfig, HistoryManager};
+use crate::output::{Output, QuietOutput, StdoutOutput};
+use crate::value::Value;
+use crate::variables::{Attributes, ScopeKind, VariableStore, VARS_FILE};
+use crate::aliases::{AliasStore, ALIAS_FILE};
+use crate::parser::{env_reference, expand_substitutions, parse_command_with, references_history, split_chain, split_pipeline};
+use crate::command::{ShowSubcommand, ClearTarget, HistorySubcommand, AliasSubcommand, NumericType, Connector};
+use crate::expr::{evaluate, evaluate_float};
+use crate::filter::Filter;
+use crate::signal;
+use crate::editor::{LineEditor, LineInput};
+use crate::prompt::{render, PromptContext};
+use crate::completion::CommandCompleter;
+use crate::registry::{find_command, help_entries};
+use crate::plugin::{CommandContext, CommandHandler, PluginCommand};
+
+/// Help rows for command chaining; descriptions are message ids
+const CHAIN_HELP: &[(&str, &str)] = &[
+    ("a ; b", "help.chain.always"),
+    ("a && b", "help.chain.and"),
+    ("a || b", "help.chain.or"),
+];
+
+/// Help rows for pipelines
+const PIPE_HELP: &[(&str, &str)] = &[
+    ("a | b", "help.pipe.pass"),
+    ("a | set name", "help.pipe.set"),
+];
+
+/// Help rows for history commands
+const HISTORY_HELP: &[(&str, &str)] = &[
+    ("!!", "help.history.last"),
+    ("! n", "help.history.id"),
+    ("! -n", "help.history.offset"),
+    ("history | hist", "help.history.list"),
+    ("history | hist n", "help.history.recent"),
+    ("history | hist search str", "help.history.search"),
+    ("history | hist clear", "help.history.clear"),
+];
+
+/// Help rows for expressions
+const EXPR_HELP: &[(&str, &str)] = &[
+    ("+ - * / % **  ( )", "help.expr.arith"),
+    ("== != < <= > >=  && || !", "help.expr.logic"),
+];
+
+/// Help rows for variable references
+const VARIABLE_HELP: &[(&str, &str)] = &[
+    ("$name | ${name}", "help.variables.expand"),
+    ("$ENV{NAME}", "help.variables.env"),
+    ("$(command)", "help.variables.capture"),
+];
+
+fn help_rows(rows: &[(&str, &'static str)]) -> Vec<(String, String)> {
+    rows.iter().map(|(left, id)| (left.to_string(), i18n::text(id).to_string())).collect()
+}
+
+/// One line of a variable listing, with the attributes that are set
+fn variable_row(name: &str, value: &Value, attrs: Attributes) -> String {
+    let mut row = format!("  {:15} = {:20} ({})", name, value.literal(), value.type_name());
+    let names = attrs.names();
+    if !names.is_empty() {
+        row.push_str(&format!(" [{}]", names.join(", ")));
+    }
+    row.push('\n');
+    row
+}
+
+/// Exit code of a finished program; like shells, a program killed by a
+/// signal reports 128 plus the signal number
+fn exit_code(status: ExitStatus) -> i32 {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return 128 + signal;
+        }
+    }
+    status.code().unwrap_or(1)
+}
+
+/// Maximum nesting depth of `source` commands
+const MAX_SOURCE_DEPTH: usize = 32;
+
+/// Maximum nesting depth of history replays (`!!`, `! n`), reached through
+/// aliases that refer back to history
+const MAX_REPLAY_DEPTH: usize = 8;
 
 /// Main TypeCmd application
 pub struct TypeCmd {
     variables: VariableStore,
-    variables_int: VariableStoreInt,
     history: HistoryManager,
     version: String,
+    config: Config,
+    source_depth: usize,
+    /// Nesting depth of history replays
+    replay_depth: usize,
+    last_status: i32,
+    output: Rc<dyn Output>,
+    exit_code: Option<i32>,
+    editor: LineEditor,
+    plugins: Vec<PluginCommand>,
+    aliases: AliasStore,
+    /// Aliases currently being expanded, to stop recursive expansion
+    expanding_aliases: Vec<String>,
+    /// Set while the startup commands run, whose aliases are not saved
+    starting: bool,
+    /// Variable revision at the last autosave, `None` while autosave is off
+    vars_saved: Option<u64>,
+    /// Set while running a `$(...)` command, so programs write their output
+    /// into the result instead of the terminal
+    capturing: bool,
+    /// Exit code of the last external program run by the current line
+    child_status: Option<i32>,
+    /// Output of the previous pipeline stage, consumed by the next command
+    pipe_input: Option<String>,
 }
 
 impl TypeCmd {
     /// Create a new TypeCmd instance
     pub fn new() -> Result<Self> {
-        let history = HistoryManager::new()?;
+        Self::with_config(Config::default())
+    }
+    
+    /// Create a new TypeCmd instance with a custom history configuration
+    pub fn with_history_config(history: HistoryConfig) -> Result<Self> {
+        Self::with_config(Config { history, ..Config::default() })
+    }
+    
+    /// Create a new TypeCmd instance from a startup configuration and run
+    /// its startup commands. Only warnings and errors from the startup
+    /// commands are shown; failing commands are skipped.
+    pub fn with_config(mut config: Config) -> Result<Self> {
+        let startup = std::mem::take(&mut config.startup);
+        let history = HistoryManager::with_config(config.history.clone())?;
+        // Saved aliases are always available; `persist` only decides whether
+        // changes are written back
+        let aliases = AliasStore::with_file(
+            history.history_path().with_file_name(ALIAS_FILE),
+            config.history.persist,
+        )?;
         
-        Ok(TypeCmd {
+        let mut typecmd = TypeCmd {
             variables: VariableStore::new(),
-            variables_int: VariableStoreInt::new(), 
             history,
             version: "0.5.0".to_string(),
-        })
+            config,
+            source_depth: 0,
+            replay_depth: 0,
+            last_status: 0,
+            output: Rc::new(StdoutOutput),
+            exit_code: None,
+            editor: LineEditor::new(),
+            plugins: Vec::new(),
+            aliases,
+            expanding_aliases: Vec::new(),
+            starting: false,
+            vars_saved: None,
+            capturing: false,
+            child_status: None,
+            pipe_input: None,
+        };
+        
+        if !startup.is_empty() {
+            // The file name only labels errors; configurations built with
+            // `Config::parse` have no path
+            let file = match &typecmd.config.path {
+                Some(path) => path.display().to_string(),
+                None => CONFIG_FILE.to_string(),
+            };
+            // Only problems in the startup commands are worth showing
+            typecmd.starting = true;
+            let result = typecmd.quietly(|typecmd| typecmd.run_script_lines(&file, &startup, true));
+            typecmd.starting = false;
+            result?;
+        }
+        
+        // Saved variables win over the ones set by the startup commands
+        if typecmd.config.autosave_vars {
+            let path = typecmd.vars_path();
+            if path.exists() {
+                typecmd.load_variables(&path)?;
+            }
+            typecmd.vars_saved = Some(typecmd.variables.revision());
+        }
+        Ok(typecmd)
+    }
+    
+    /// Effective configuration
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+    
+    /// Register a custom command under `name` and `aliases`.
+    ///
+    /// Fails with `CommandExists` if any of the names is already taken by a
+    /// built-in or previously registered command.
+    pub fn register_command(
+        &mut self,
+        name: &str,
+        aliases: &[&str],
+        handler: Box<dyn CommandHandler>,
+    ) -> Result<()> {
+        for candidate in std::iter::once(&name).chain(aliases) {
+            let lower = candidate.to_lowercase();
+            if find_command(&lower).is_some() || self.find_plugin(&lower).is_some() {
+                return Err(TypeCmdError::CommandExists(candidate.to_string()));
+            }
+        }
+        
+        self.plugins.push(PluginCommand {
+            name: name.to_lowercase(),
+            aliases: aliases.iter().map(|alias| alias.to_lowercase()).collect(),
+            handler,
+        });
+        Ok(())
+    }
+    
+    fn find_plugin(&self, name: &str) -> Option<usize> {
+        self.plugins.iter().position(|plugin| plugin.matches(name))
+    }
+    
+    /// Replace the sink that command messages are written to
+    pub fn set_output(&mut self, output: Box<dyn Output>) {
+        self.output = output.into();
+    }
+    
+    /// Show a status message and return it as the result. While capturing,
+    /// the result is empty, so `$(...)` and pipelines only get data.
+    fn status(&self, msg: String) -> Result<Option<String>> {
+        self.output.success(&msg);
+        Ok((!self.capturing).then_some(msg))
+    }
+    
+    /// Like `status`, for an informational message such as an empty listing
+    fn notice(&self, msg: String) -> Result<Option<String>> {
+        self.output.info(&msg);
+        Ok((!self.capturing).then_some(msg))
+    }
+    
+    /// Run `f` showing only warnings and errors
+    fn quietly<T>(&mut self, f: impl FnOnce(&mut Self) -> T) -> T {
+        let output = Rc::clone(&self.output);
+        self.output = Rc::new(QuietOutput(Box::new(Rc::clone(&output))));
+        let result = f(self);
+        self.output = output;
+        result
+    }
+    
+    /// Exit code requested by an `exit` command, if any.
+    ///
+    /// `exit` never terminates the process; hosts should check this after
+    /// executing commands and decide what exiting means for them.
+    pub fn exit_requested(&self) -> Option<i32> {
+        self.exit_code
+    }
+    
+    /// Exit status of the last executed command line (0 on success, 1 on failure)
+    pub fn last_status(&self) -> i32 {
+        self.last_status
+    }
+
+    /// Expand undefined `$name` references to an empty string instead of
+    /// failing with `UndefinedVariable`
+    pub fn set_undefined_as_empty(&mut self, enabled: bool) {
+        self.config.undefined_as_empty = enabled;
+    }
+
+    /// Resolve a `$name` reference against the variable store, or a
+    /// `$ENV{NAME}` reference against the process environment
+    fn expand_variable(&self, name: &str) -> Result<String> {
+        if let Some(var) = env_reference(name) {
+            return match env::var(var) {
+                Ok(value) => Ok(value),
+                Err(_) if self.config.undefined_as_empty => Ok(String::new()),
+                Err(_) => Err(TypeCmdError::Other(tr!("env.not_set", var))),
+            };
+        }
+        match self.variables.get(name) {
+            Some(value) => Ok(value.to_string()),
+            None if self.config.undefined_as_empty => Ok(String::new()),
+            None => Err(TypeCmdError::UndefinedVariable(name.to_string())),
+        }
     }
     
-    /// Execute a command string
+    /// Execute a command string, which may chain several commands with
+    /// `;`, `&&` and `||`. Returns the result of the last command run.
     pub fn execute_command(&mut self, input: &str) -> Result<Option<String>> {
-        let tokens = parse_command(input)?;
-        let command = parse_to_command(tokens)?;
-        
-        match command {
-            Command::Show(subcmd) => self.handle_show(subcmd),
-            Command::Exit(code) => self.handle_exit(code),
-            Command::Set(var, value) => self.handle_set(&var, &value),
-            Command::Get(var) => self.handle_get(&var),
-            Command::Copy(var, oldvar) => self.handle_copy(&var, &oldvar),
-            Command::StringCmd(text) => self.handle_string(&text),
-            Command::IntCmd(num) => self.handle_int(num),
-            Command::List => self.handle_list(),
-            Command::Delete(var) => self.handle_delete(&var),
-            Command::Clear(target) => self.handle_clear(target),
-            Command::History(subcmd) => self.handle_history(subcmd),
-            Command::LastCommand => self.handle_last_command(),
-            Command::HistoryCommand(spec) => self.handle_history_command(&spec),
-            Command::ISet(var, val) => self.handle_iset(&var, val),
-        }
-    }
-    
-    fn handle_show(&self, subcmd: ShowSubcommand) -> Result<Option<String>> {
+        let chain = split_chain(input)?;
+        let mut result = Ok(None);
+        
+        for (connector, segment) in chain {
+            if self.exit_code.is_some() {
+                break;
+            }
+            
+            let should_run = match connector {
+                Connector::Always => true,
+                Connector::And => result.is_ok(),
+                Connector::Or => result.is_err(),
+            };
+            if !should_run {
+                continue;
+            }
+            
+            // The previous failure is superseded, report it before moving on
+            if let Err(e) = &result {
+                self.output.error(&format!("{}", e));
+            }
+            self.child_status = None;
+            result = self.execute_single(&segment);
+        }
+        
+        // A failing program reports its own exit code
+        self.last_status = match &result {
+            Ok(_) => 0,
+            Err(_) => self.child_status.filter(|&code| code != 0).unwrap_or(1),
+        };
+        self.autosave_variables();
+        result
+    }
+    
+    /// Save the variables to the default file if autosave is on and they
+    /// changed since the last save
+    fn autosave_variables(&mut self) {
+        let revision = self.variables.revision();
+        if self.vars_saved.is_none_or(|saved| saved == revision) {
+            return;
+        }
+        // Record the attempt even on failure, so the warning is shown once
+        // per change rather than after every command
+        self.vars_saved = Some(revision);
+        if let Err(e) = self.variables.save_file(&self.vars_path()) {
+            self.output.warn(&tr!("config.autosave_failed", e));
+        }
+    }
+    
+    /// Run a command line for `$(...)` or a pipeline stage and return its
+    /// result instead of showing it. `input` is piped into the command.
+    fn capture(&mut self, command: &str, input: Option<String>) -> Result<String> {
+        let capturing = std::mem::replace(&mut self.capturing, true);
+        let pipe_input = std::mem::replace(&mut self.pipe_input, input);
+        // `exit` only ends the captured command, not the session
+        let exit_code = self.exit_code.take();
+        let result = self.quietly(|typecmd| typecmd.execute_command(command));
+        self.capturing = capturing;
+        self.pipe_input = pipe_input;
+        self.exit_code = exit_code;
+        Ok(result?.unwrap_or_default())
+    }
+    
+    /// Run the stages of a pipeline, passing each result to the next stage.
+    /// Only the last stage shows its output. Every stage goes through alias
+    /// expansion, and input piped into the pipeline feeds its first stage.
+    fn execute_pipeline(&mut self, stages: &[String]) -> Result<Option<String>> {
+        let Some((last, rest)) = stages.split_last() else {
+            return Ok(None);
+        };
+        let mut input = self.pipe_input.take();
+        for stage in rest {
+            input = Some(self.capture(stage, input)?);
+        }
+        
+        self.pipe_input = input;
+        let result = self.execute_single(last);
+        self.pipe_input = None;
+        result
+    }
+    
+    /// Take the piped input of a command that needs it
+    fn take_pipe_input(&mut self, name: &str) -> Result<String> {
+        self.pipe_input
+            .take()
+            .ok_or_else(|| TypeCmdError::Other(tr!("pipe.no_input", name, name)))
+    }
+    
+    /// Run an external program with the exported variables added to its
+    /// environment.
+    ///
+    /// Piped input is written to the program's standard input. Output goes
+    /// straight to the terminal, or becomes the result while capturing. A
+    /// non-zero exit status is an error; the code becomes the status of the
+    /// command line.
+    pub(crate) fn run_program(&mut self, args: &[String]) -> Result<Option<String>> {
+        let (program, args) = args.split_first()
+            .ok_or_else(|| TypeCmdError::InsufficientArgs(tr!("args.run_program")))?;
+        let mut command = process::Command::new(program);
+        command.args(args);
+        for (name, value) in self.variables.exported() {
+            command.env(name, value.to_string());
+        }
+        
+        let spawn_error = |e: io::Error| match e.kind() {
+            ErrorKind::NotFound => TypeCmdError::CommandNotFound(program.clone()),
+            _ => TypeCmdError::Other(tr!("run.failed", program, e)),
+        };
+        let input = self.pipe_input.take();
+        if input.is_some() {
+            command.stdin(Stdio::piped());
+        }
+        if self.capturing {
+            command.stdout(Stdio::piped());
+        }
+        let mut child = command.spawn().map_err(spawn_error)?;
+        if let Some(input) = input
+            && let Some(mut stdin) = child.stdin.take()
+        {
+            // Write from another thread so a program that fills its output
+            // pipe before reading all input cannot deadlock us
+            thread::spawn(move || {
+                let _ = stdin.write_all(input.as_bytes());
+            });
+        }
+        let output = child.wait_with_output()?;
+        let captured = self.capturing.then(|| String::from_utf8_lossy(&output.stdout).into_owned());
+        
+        let code = exit_code(output.status);
+        self.child_status = Some(code);
+        if code != 0 {
+            return Err(TypeCmdError::Other(tr!("run.exit_status", program, code)));
+        }
+        Ok(captured)
+    }
+    
+    /// Expand an alias in the first word of `input`, skipping aliases
+    /// that are already being expanded
+    fn expand_alias(&self, input: &str) -> Option<(String, String)> {
+        let input = input.trim_start();
+        let (first, rest) = input.split_once(char::is_whitespace).unwrap_or((input, ""));
+        if self.expanding_aliases.iter().any(|name| name == first) {
+            return None;
+        }
+        
+        let expansion = self.aliases.get(first)?;
+        let line = if rest.is_empty() {
+            expansion.clone()
+        } else {
+            format!("{} {}", expansion, rest)
+        };
+        Some((first.to_string(), line))
+    }
+    
+    /// Execute a single command without chaining
+    fn execute_single(&mut self, input: &str) -> Result<Option<String>> {
+        if let Some((name, line)) = self.expand_alias(input) {
+            // The expansion may itself be a chain, so run it as a full line
+            self.expanding_aliases.push(name);
+            let result = self.execute_command(&line);
+            self.expanding_aliases.pop();
+            return result;
+        }
+        
+        let stages = split_pipeline(input)?;
+        if stages.len() > 1 {
+            return self.execute_pipeline(&stages);
+        }
+        self.execute_stage(input)
+    }
+    
+    /// Execute one command of a pipeline, after alias expansion
+    fn execute_stage(&mut self, input: &str) -> Result<Option<String>> {
+        let input = expand_substitutions(input, |command| self.capture(command, None))?;
+        let tokens = parse_command_with(&input, |name| self.expand_variable(name))?;
+        
+        if let Some(index) = tokens.first().and_then(|name| self.find_plugin(&name.to_lowercase())) {
+            let mut ctx = CommandContext {
+                variables: &mut self.variables,
+                history: &mut self.history,
+                output: self.output.as_ref(),
+            };
+            return self.plugins[index].handler.execute(&tokens[1..], &mut ctx);
+        }
+        
+        let Some(name) = tokens.first().map(|name| name.to_lowercase()) else {
+            return Err(TypeCmdError::Parse(tr!("parse.empty_command")));
+        };
+        let Some(spec) = find_command(&name) else {
+            if self.config.run_fallback {
+                return self.run_program(&tokens);
+            }
+            return Err(TypeCmdError::CommandNotFound(name));
+        };
+        
+        let command = (spec.parse)(&tokens[1..])?;
+        (spec.run)(self, command)
+    }
+    
+    pub(crate) fn handle_show(&self, subcmd: ShowSubcommand) -> Result<Option<String>> {
         match subcmd {
-            ShowSubcommand::Help => self.show_help(),
+            ShowSubcommand::Help(None) => self.show_help(),
+            ShowSubcommand::Help(Some(name)) => self.show_command_help(&name),
             ShowSubcommand::Version => self.show_version(),
             ShowSubcommand::Variables => self.show_variables(),
+            ShowSubcommand::Scopes => self.show_scopes(),
             ShowSubcommand::History(limit) => self.show_history(limit),
             ShowSubcommand::License => self.show_license(),
+            ShowSubcommand::Aliases => self.show_aliases(),
+            ShowSubcommand::Config => self.show_config(),
+        }
+    }
+
+    fn show_config(&self) -> Result<Option<String>> {
+        let source = match &self.config.path {
+            Some(path) => path.display().to_string(),
+            None => tr!("config.no_file"),
+        };
+        let mut output = tr!("config.header", source);
+        output.push('\n');
+        for (key, value) in self.config.entries() {
+            output.push_str(&format!("  {:20} = {}\n", key, value));
+        }
+        
+        self.output.info(&output);
+        Ok(Some(output))
+    }
+    
+    pub(crate) fn handle_config(&mut self, key: &str, value: &str) -> Result<Option<String>> {
+        self.config.set(key, value)?;
+        let key = key.to_lowercase();
+        match key.as_str() {
+            "color" => set_color_enabled(self.config.color && terminal_supports_color()),
+            "lang" => set_lang(self.config.lang.unwrap_or_else(Lang::from_env)),
+            _ if key.starts_with("theme") => set_theme(self.config.theme.clone()),
+            _ if key.starts_with("history.") => {
+                self.output.warn(&tr!("config.next_start", key));
+            }
+            "vars.autosave" => {
+                // Turning autosave on saves the current variables right away
+                self.vars_saved = None;
+                if self.config.autosave_vars {
+                    self.variables.save_file(&self.vars_path())?;
+                    self.vars_saved = Some(self.variables.revision());
+                }
+            }
+            _ => {}
         }
+        
+        let msg = tr!("config.set", key, value);
+        self.status(msg)
     }
 
+    /// Default variable file, next to the history file
+    fn vars_path(&self) -> PathBuf {
+        self.history.history_path().with_file_name(VARS_FILE)
+    }
+    
+    pub(crate) fn handle_save_vars(&self, file: Option<&str>) -> Result<Option<String>> {
+        let path = file.map_or_else(|| self.vars_path(), PathBuf::from);
+        self.variables.save_file(&path)?;
+        
+        let msg = tr!("vars.saved", self.variables.len(), path.display());
+        self.status(msg)
+    }
+    
+    pub(crate) fn handle_load_vars(&mut self, file: Option<&str>) -> Result<Option<String>> {
+        let path = file.map_or_else(|| self.vars_path(), PathBuf::from);
+        let loaded = self.load_variables(&path)?;
+        
+        let msg = tr!("vars.loaded", path.display(), loaded);
+        self.status(msg)
+    }
+    
+    pub(crate) fn handle_export_json(&self, file: Option<&str>) -> Result<Option<String>> {
+        let json = self.variables.to_json()?;
+        let Some(file) = file else {
+            self.output.info(&json);
+            return Ok(Some(json));
+        };
+        
+        fs::write(file, format!("{}\n", json))?;
+        let result = self.status(tr!("vars.exported", self.variables.len(), file));
+        let with_attributes = self.variables
+            .sorted()
+            .into_iter()
+            .filter(|(name, _)| self.variables.attributes(name).is_some_and(|attrs| attrs != Attributes::default()))
+            .count();
+        if with_attributes > 0 {
+            self.output.warn(&tr!("vars.json_no_attributes", with_attributes));
+        }
+        result
+    }
+    
+    pub(crate) fn handle_import_json(&mut self, file: &str, replace: bool) -> Result<Option<String>> {
+        // Parse the whole document first, so a bad file changes nothing
+        let imported = VariableStore::from_json(&fs::read_to_string(file)?)?;
+        let mut variables = self.variables.clone();
+        if replace {
+            variables.clear();
+        }
+        let count = variables.merge(imported)?;
+        self.variables = variables;
+        
+        let msg = if replace {
+            tr!("vars.replaced", file, count)
+        } else {
+            tr!("vars.imported", file, count)
+        };
+        self.status(msg)
+    }
+    
+    pub(crate) fn handle_env(&self, name: Option<&str>) -> Result<Option<String>> {
+        if let Some(name) = name {
+            let value = env::var(name).map_err(|_| TypeCmdError::Other(tr!("env.not_set", name)))?;
+            self.output.info(&tr!("env.value", name, value));
+            return Ok(Some(value));
+        }
+        
+        let mut vars: Vec<_> = env::vars_os()
+            .filter_map(|(name, value)| Some((name.into_string().ok()?, value.into_string().ok()?)))
+            .collect();
+        vars.sort();
+        let mut output = tr!("env.header", vars.len());
+        output.push('\n');
+        for (name, value) in vars {
+            output.push_str(&format!("  {}={}\n", name, value));
+        }
+        
+        self.output.info(&output);
+        Ok(Some(output))
+    }
+    
+    /// Copy environment variables into the store as exported string
+    /// variables; `None` copies the whole environment
+    pub(crate) fn handle_import_env(&mut self, names: Option<Vec<String>>) -> Result<Option<String>> {
+        let vars: Vec<(String, String)> = match names {
+            // Variables that are not valid UTF-8 cannot be stored
+            None => env::vars_os()
+                .filter_map(|(name, value)| Some((name.into_string().ok()?, value.into_string().ok()?)))
+                .collect(),
+            Some(names) => names.into_iter()
+                .map(|name| match env::var(&name) {
+                    Ok(value) => Ok((name, value)),
+                    Err(_) => Err(TypeCmdError::Other(tr!("env.not_set", name))),
+                })
+                .collect::<Result<_>>()?,
+        };
+        
+        // Work on a copy, so a read-only conflict imports nothing
+        let mut variables = self.variables.clone();
+        for (name, value) in &vars {
+            variables.set(name.clone(), Value::Str(value.clone()))?;
+            variables.export(name)?;
+        }
+        self.variables = variables;
+        
+        let msg = tr!("env.imported", vars.len());
+        self.status(msg)
+    }
+    
+    pub(crate) fn handle_export(&mut self, names: &[String]) -> Result<Option<String>> {
+        let mut variables = self.variables.clone();
+        for name in names {
+            variables.export(name)?;
+        }
+        self.variables = variables;
+        
+        let msg = tr!("vars.exported_env", names.join(", "));
+        self.status(msg)
+    }
+    
+    /// Load a variable file, warning about skipped lines. Returns the
+    /// number of loaded variables.
+    fn load_variables(&mut self, path: &Path) -> Result<usize> {
+        let (loaded, skipped) = self.variables.load_file(path)?;
+        if skipped > 0 {
+            self.output.warn(&tr!("vars.skipped", path.display(), skipped));
+        }
+        Ok(loaded)
+    }
+    
+    fn show_aliases(&self) -> Result<Option<String>> {
+        if self.aliases.is_empty() {
+            let msg = tr!("alias.none");
+            return self.notice(msg);
+        }
+        
+        let mut output = tr!("alias.header", self.aliases.len());
+        output.push('\n');
+        for (name, expansion) in self.aliases.all() {
+            output.push_str(&format!("  {:15} = '{}'\n", name, expansion));
+        }
+        
+        self.output.info(&output);
+        Ok(Some(output))
+    }
+    
+    pub(crate) fn handle_alias(&mut self, subcmd: AliasSubcommand) -> Result<Option<String>> {
+        match subcmd {
+            AliasSubcommand::List => self.show_aliases(),
+            AliasSubcommand::Show(name) => match self.aliases.get(&name) {
+                Some(expansion) => {
+                    let msg = format!("{}='{}'", name, expansion);
+                    self.output.info(&msg);
+                    Ok(Some(msg))
+                }
+                None => Err(TypeCmdError::Other(tr!("alias.not_found", name))),
+            },
+            AliasSubcommand::Define(name, expansion) => {
+                let msg = tr!("alias.set", name, expansion);
+                // Startup commands run on every start, so saving their
+                // aliases would bring back ones that were removed later
+                if self.starting {
+                    self.aliases.set_session(name, expansion)?;
+                } else {
+                    self.aliases.set(name, expansion)?;
+                }
+                self.status(msg)
+            }
+            AliasSubcommand::Remove(name) => {
+                if self.aliases.remove(&name)? {
+                    let msg = tr!("alias.removed", name);
+                    self.status(msg)
+                } else {
+                    Err(TypeCmdError::Other(tr!("alias.not_found", name)))
+                }
+            }
+        }
+    }
+    
+    pub(crate) fn handle_theme(&mut self, name: Option<&str>) -> Result<Option<String>> {
+        if let Some(name) = name {
+            self.config.set("theme", name)?;
+            set_theme(self.config.theme.clone());
+            let msg = tr!("theme.switched", self.config.theme.name);
+            return self.status(msg);
+        }
+        
+        let current = theme();
+        let mut output = tr!("theme.available");
+        output.push('\n');
+        for name in THEME_NAMES {
+            let marker = if *name == current.name { "*" } else { " " };
+            output.push_str(&format!(" {} {}\n", marker, name));
+        }
+        output.push('\n');
+        output.push_str(&tr!("theme.styles"));
+        output.push('\n');
+        for role in Role::ALL {
+            let sample = paint(role.name(), role);
+            let padding = " ".repeat(10usize.saturating_sub(role.name().len()));
+            output.push_str(&format!("  {}{}{}\n", sample, padding, current.style(role)));
+        }
+        
+        self.output.info(&output);
+        Ok(Some(output))
+    }
+    
     fn show_license(&self) -> Result<Option<String>>{
         let shows = "MIT license";
-        println!("LICENSE: {}", shows);
+        self.output.info(&format!("LICENSE: {}", shows));
         Ok(Some(shows.to_string()))
     }
     
+    pub(crate) fn handle_filter(&mut self, filter: &Filter) -> Result<Option<String>> {
+        let input = self.take_pipe_input(filter.name())?;
+        let result = filter.apply(&input);
+        self.output.info(&result);
+        Ok(Some(result))
+    }
+    
     fn show_help(&self) -> Result<Option<String>> {
-        let help_text = format!(
-            "{}{}TypeCmd 命令行模拟器{}\n\
-            版本: {}\n\
-            历史记录: {} 条命令\n\n{}\
-            {}基础命令:\n\
-              show                             - 显示信息: show [help|ver|vars|history|license]\n\
-              exit    | quit  | q              - 退出程序\n\
-              to      | var   | let   | set    - 设置变量: to <变量名> <值>\n\
-              ito     | ivar  | ilet  | iset   - 设置整数变量: ito <变量名> <值>\n\
-              get     | which | echo           - 获取变量: get <变量名>\n\
-              copy    | cpvar                  - 复制变量: copy <新变量名> <旧变量名>\n\
-              string  | str                    - 字符串输出: string <文本>\n\
-              int     | num                    - 数字处理: int <数字>\n\
-              list    | ls                     - 列出所有变量\n\
-              rm      | del   | unset          - 删除变量: rm <变量名>\n\
-              clear   | cls                    - 清空所有变量或历史\n\
-              history | hist                   - 显示历史命令\n\
-              version | ver                    - 等同于show ver\n\
-            历史命令使用:\n\
-              !!                               - 执行上一条命令\n\
-              ! n                              - 执行历史第n条命令\n\
-              ! -n                             - 执行历史倒数第n条命令\n\
-              history | hist                   - 显示所有历史命令\n\
-              history | hist n                 - 显示最近n条历史命令\n\
-              history | hist search str        - 搜索包含str的历史命令\n\
-              history | hist clear             - 清除所有历史记录\n\
-            {}",
-
-            bold(&BLUE.to_string()), BLUE, RESET,
-            self.version,
-            self.history.count(), BLUE, 
-            BLUE, RESET,
+        let plugins = self.plugins
+            .iter()
+            .map(|plugin| (plugin.names(), plugin.handler.summary().to_string()))
+            .collect();
+        let sections: [(&str, Vec<(String, String)>); 7] = [
+            ("help.section.builtin", help_entries()),
+            ("help.section.plugins", plugins),
+            ("help.section.chain", help_rows(CHAIN_HELP)),
+            ("help.section.pipe", help_rows(PIPE_HELP)),
+            ("help.section.history", help_rows(HISTORY_HELP)),
+            ("help.section.expr", help_rows(EXPR_HELP)),
+            ("help.section.variables", help_rows(VARIABLE_HELP)),
+        ];
+        let width = sections
+            .iter()
+            .flat_map(|(_, rows)| rows.iter().map(|(left, _)| left.chars().count()))
+            .max()
+            .unwrap_or(0);
+        
+        let mut help_text = format!(
+            "{}\n{}\n",
+            bold(&tr!("help.title")),
+            tr!("help.header", self.version, self.history.count()),
         );
-        print_info(&help_text);
+        for (title, rows) in sections.iter().filter(|(_, rows)| !rows.is_empty()) {
+            help_text.push_str(&format!("\n{}:\n", i18n::text(title)));
+            for (left, description) in rows {
+                help_text.push_str(&format!("  {:width$} - {}\n", left, description, width = width));
+            }
+        }
+        
+        self.output.info(&help_text);
+        Ok(Some(help_text))
+    }
+    
+    fn show_command_help(&self, name: &str) -> Result<Option<String>> {
+        let lower = name.to_lowercase();
+        let help_text = match (find_command(&lower), self.find_plugin(&lower)) {
+            (Some(spec), _) => spec.help(),
+            (None, Some(index)) => self.plugins[index].help(),
+            (None, None) => return Err(TypeCmdError::CommandNotFound(name.to_string())),
+        };
+        self.output.info(&help_text);
         Ok(Some(help_text))
     }
     
     fn show_version(&self) -> Result<Option<String>> {
         let msg = format!("TypeCmd Version {}", self.version);
-        print_info(&msg);
+        self.output.info(&msg);
         Ok(Some(msg))
     }
     
     fn show_variables(&self) -> Result<Option<String>> {
-        let vars = self.variables.all();
+        let vars = self.variables.listed();
         
         if vars.is_empty() {
-            let msg = "没有定义的变量";
-            print_info(msg);
-            return Ok(Some(msg.to_string()));
+            let msg = tr!("vars.none");
+            return self.notice(msg);
         }
         
-        let mut output = format!("已定义的变量 (共{}个):\n", vars.len());
-        for (key, value) in vars {
-            output.push_str(&format!("  {:15} = \"{}\"\n", key, value));
+        let mut output = tr!("vars.header", vars.len());
+        output.push('\n');
+        for (key, value, attrs) in vars {
+            output.push_str(&variable_row(key, value, attrs));
+        }
+        
+        self.output.info(&output);
+        Ok(Some(output))
+    }
+    
+    fn show_scopes(&self) -> Result<Option<String>> {
+        let mut output = String::new();
+        // Innermost first, the order lookups search in
+        for scope in self.variables.scopes().iter().rev() {
+            let vars = scope.listed();
+            output.push_str(&tr!("vars.scope_header", scope.kind.name(), vars.len()));
+            output.push('\n');
+            for (key, value, attrs) in vars {
+                output.push_str(&variable_row(key, value, attrs));
+            }
         }
         
-        print_info(&output);
+        self.output.info(&output);
         Ok(Some(output))
     }
     
@@ -135,18 +862,18 @@ impl TypeCmd {
         let entries = self.history.get(limit);
         
         if entries.is_empty() {
-            let msg = "历史记录为空";
-            print_info(msg);
-            return Ok(Some(msg.to_string()));
+            let msg = tr!("history.empty");
+            return self.notice(msg);
         }
         
         let limit_str = if let Some(l) = limit {
-            format!("最近{}条", l)
+            tr!("history.recent", l)
         } else {
-            "所有".to_string()
+            tr!("history.all")
         };
         
-        let mut output = format!("历史命令 ({}):\n", limit_str);
+        let mut output = tr!("history.header", limit_str);
+        output.push('\n');
         for entry in entries.iter().rev() {
             let time_str = entry.timestamp.format("%H:%M:%S").to_string();
             output.push_str(&format!(
@@ -155,38 +882,111 @@ impl TypeCmd {
             ));
         }
         
-        output.push_str("\n使用 !<编号> 执行历史命令");
+        output.push('\n');
+        output.push_str(&tr!("history.hint"));
         
-        print_info(&output);
+        self.output.info(&output);
         Ok(Some(output))
     }
     
-    fn handle_exit(&self, code: Option<i32>) -> Result<Option<String>> {
+    pub(crate) fn handle_exit(&mut self, code: Option<i32>) -> Result<Option<String>> {
         let exit_code = code.unwrap_or(0);
-        print_success(&format!("再见! (退出码: {})", exit_code));
-        exit(exit_code);
+        self.exit_code = Some(exit_code);
+        self.status(tr!("exit.bye", exit_code))
     }
     
-    fn handle_set(&mut self, var: &str, value: &str) -> Result<Option<String>> {
-        self.variables.set(var.to_string(), value.to_string());
-        let msg = format!("变量 \"{}\" 已设置为 \"{}\"", var, value);
-        print_success(&msg);
-        Ok(Some(msg))
+    pub(crate) fn handle_set(&mut self, var: &str, value: Value) -> Result<Option<String>> {
+        self.variables.set(var.to_string(), value)?;
+        let value = self.variables.get(var).expect("variable was just set");
+        let msg = tr!("vars.set", var, value, value.type_name());
+        self.status(msg)
     }
-
-    fn handle_iset(&mut self, var: &str, val: i64) -> Result<Option<String>> {
-        self.variables_int.set(var.to_string(), val);
-        let msg = format!("变量 \"{}\" 已设置为 \"{}\"", var, val);
-        print_success(&msg);
+    
+    /// `set name` at the end of a pipeline; trailing newlines are dropped
+    pub(crate) fn handle_set_input(&mut self, var: &str) -> Result<Option<String>> {
+        let input = self.pipe_input
+            .take()
+            .ok_or_else(|| TypeCmdError::InsufficientArgs(tr!("args.at_least_two", "set")))?;
+        let value = input.trim_end_matches(['\n', '\r']).to_string();
+        self.handle_set(var, Value::Str(value))
+    }
+    
+    pub(crate) fn handle_local(&mut self, var: &str, value: Value) -> Result<Option<String>> {
+        self.variables.set_local(var.to_string(), value)?;
+        let value = self.variables.get(var).expect("variable was just set");
+        let msg = tr!("vars.local_set", var, value, value.type_name());
+        self.status(msg)
+    }
+    
+    pub(crate) fn handle_readonly(&mut self, var: &str, value: Option<Value>) -> Result<Option<String>> {
+        let defines = value.is_some();
+        if let Some(value) = value {
+            self.variables.set(var.to_string(), value)?;
+        }
+        let mut attrs = self.variables.attributes(var)
+            .ok_or_else(|| TypeCmdError::UndefinedVariable(var.to_string()))?;
+        attrs.readonly = true;
+        self.variables.set_attributes(var, attrs)?;
+        
+        let msg = if defines {
+            let value = self.variables.get(var).expect("variable was just set");
+            tr!("vars.const_set", var, value, value.type_name())
+        } else {
+            tr!("vars.readonly_set", var)
+        };
+        self.status(msg)
+    }
+    
+    pub(crate) fn handle_attr(&mut self, var: &str, flags: &[(String, bool)]) -> Result<Option<String>> {
+        let mut attrs = self.variables.attributes(var)
+            .ok_or_else(|| TypeCmdError::UndefinedVariable(var.to_string()))?;
+        for (name, on) in flags {
+            *attrs.flag_mut(name).expect("attribute names are checked by the parser") = *on;
+        }
+        if !flags.is_empty() {
+            self.variables.set_attributes(var, attrs)?;
+        }
+        
+        let names = attrs.names();
+        let names = if names.is_empty() {
+            tr!("vars.no_attributes")
+        } else {
+            names.join(", ")
+        };
+        let msg = tr!("vars.attributes", var, names);
+        self.output.info(&msg);
         Ok(Some(msg))
     }
     
-    fn handle_get(&self, var: &str) -> Result<Option<String>> {
+    pub(crate) fn handle_set_expr(&mut self, var: &str, expr: &str, ty: NumericType) -> Result<Option<String>> {
+        let result = match ty {
+            NumericType::Int => evaluate(expr, &self.variables)?,
+            NumericType::Float => evaluate_float(expr, &self.variables)?,
+        };
+        let value = match (ty, result) {
+            (NumericType::Int, Value::Int(n)) => Value::Int(n),
+            (NumericType::Float, Value::Int(n)) => Value::Float(n as f64),
+            (NumericType::Float, Value::Float(x)) => Value::Float(x),
+            (NumericType::Int, other) => {
+                return Err(TypeCmdError::TypeMismatch(
+                    tr!("expr.expected", other.type_name(), "int")
+                ));
+            }
+            (NumericType::Float, other) => {
+                return Err(TypeCmdError::TypeMismatch(
+                    tr!("expr.expected", other.type_name(), "float")
+                ));
+            }
+        };
+        self.handle_set(var, value)
+    }
+    
+    pub(crate) fn handle_get(&self, var: &str) -> Result<Option<String>> {
         match self.variables.get(var) {
             Some(value) => {
-                let msg = format!("变量 {} 的值为: {}", var, value);
-                print_info(&msg);
-                Ok(Some(value.clone()))
+                let msg = tr!("vars.value", var, value, value.type_name());
+                self.output.info(&msg);
+                Ok(Some(value.to_string()))
             }
             None => {
                 Err(TypeCmdError::UndefinedVariable(var.to_string()))
@@ -194,7 +994,10 @@ impl TypeCmd {
         }
     }
 
-    fn handle_copy(&mut self, var: &str, oldvar: &str) -> Result<Option<String>> {
+    pub(crate) fn handle_copy(&mut self, var: &str, oldvar: &str, extra: bool) -> Result<Option<String>> {
+        if extra {
+            self.output.warn(&tr!("args.copy_extra"));
+        }
         // 先获取值并克隆，释放不可变借用后再设置新变量
         let value = match self.variables.get(oldvar) {
             Some(value) => value.clone(),
@@ -204,65 +1007,82 @@ impl TypeCmd {
         };
 
         // 现在不可变借用已结束，可以进行可变操作
-        self.variables.set(var.to_string(), value.clone());
-        let msg = format!("变量 \"{}\" 已设置为 变量\"{}\"的值 \"{}\"", var, oldvar, value);
-        Ok(Some(msg))
+        self.variables.set(var.to_string(), value)?;
+        let value = self.variables.get(var).expect("variable was just set");
+        let msg = tr!("vars.copied", var, oldvar, value, value.type_name());
+        self.status(msg)
     }
     
-    fn handle_string(&self, text: &str) -> Result<Option<String>> {
-        print_info(text);
+    pub(crate) fn handle_string(&self, text: &str) -> Result<Option<String>> {
+        self.output.info(text);
         Ok(Some(text.to_string()))
     }
     
-    fn handle_int(&self, num: Option<i32>) -> Result<Option<String>> {
-        let num_str = num.unwrap_or(0).to_string();
-        print_info(&num_str);
+    pub(crate) fn handle_int(&self, expr: Option<&str>) -> Result<Option<String>> {
+        let num = match expr {
+            None => 0,
+            Some(expr) => match evaluate(expr, &self.variables)? {
+                Value::Int(n) => n,
+                other => {
+                    return Err(TypeCmdError::TypeMismatch(
+                        tr!("expr.expected", other.type_name(), "int")
+                    ));
+                }
+            },
+        };
+        let num_str = num.to_string();
+        self.output.info(&num_str);
         Ok(Some(num_str))
     }
     
-    fn handle_list(&self) -> Result<Option<String>> {
+    pub(crate) fn handle_calc(&self, expr: &str) -> Result<Option<String>> {
+        let result = evaluate(expr, &self.variables)?.to_string();
+        self.output.info(&result);
+        Ok(Some(result))
+    }
+    
+    pub(crate) fn handle_list(&self) -> Result<Option<String>> {
         self.show_variables()
     }
     
-    fn handle_delete(&mut self, var: &str) -> Result<Option<String>> {
-        if self.variables.delete(var) {
-            let msg = format!("已删除变量: {}", var);
-            print_success(&msg);
-            Ok(Some(msg))
+    pub(crate) fn handle_delete(&mut self, var: &str) -> Result<Option<String>> {
+        if self.variables.delete(var)? {
+            let msg = tr!("vars.deleted", var);
+            self.status(msg)
         } else {
-            let err = format!("变量不存在: {}", var);
-            print_error(&err);
+            let err = tr!("vars.not_found", var);
+            self.output.error(&err);
             Err(TypeCmdError::UndefinedVariable(var.to_string()))
         }
     }
     
-    fn handle_clear(&mut self, target: ClearTarget) -> Result<Option<String>> {
+    pub(crate) fn handle_clear(&mut self, target: ClearTarget) -> Result<Option<String>> {
         match target {
             ClearTarget::Variables => {
-                let count = self.variables.len();
-                self.variables.clear();
-                let msg = format!("已清除所有变量 (共{}个)", count);
-                print_success(&msg);
-                Ok(Some(msg))
+                let count = self.variables.clear();
+                let kept = self.variables.len();
+                if kept > 0 {
+                    self.output.warn(&tr!("vars.readonly_kept", kept));
+                }
+                let msg = tr!("vars.cleared", count);
+                self.status(msg)
             }
             ClearTarget::History => {
                 self.history.clear()?;
-                let msg = "已清除所有历史记录".to_string();
-                print_success(&msg);
-                Ok(Some(msg))
+                let msg = tr!("history.cleared_all");
+                self.status(msg)
             }
         }
     }
     
-    fn handle_history(&mut self, subcmd: HistorySubcommand) -> Result<Option<String>> {
+    pub(crate) fn handle_history(&mut self, subcmd: HistorySubcommand) -> Result<Option<String>> {
         match subcmd {
             HistorySubcommand::List(limit) => self.show_history(limit),
             HistorySubcommand::Search(keyword) => self.search_history(&keyword),
             HistorySubcommand::Clear => {
                 self.history.clear()?;
-                let msg = "历史记录已清除".to_string();
-                print_success(&msg);
-                Ok(Some(msg))
+                let msg = tr!("history.cleared");
+                self.status(msg)
             }
         }
     }
@@ -271,12 +1091,11 @@ impl TypeCmd {
         let results = self.history.search(keyword);
         
         if results.is_empty() {
-            let msg = format!("没有找到包含 \"{}\" 的历史命令", keyword);
-            print_info(&msg);
-            return Ok(Some(msg));
+            return self.notice(tr!("history.no_match", keyword));
         }
         
-        let mut output = format!("搜索 \"{}\" 的结果 ({}条):\n", keyword, results.len());
+        let mut output = tr!("history.search_header", keyword, results.len());
+        output.push('\n');
         for entry in results.iter().take(20) {
             let time_str = entry.timestamp.format("%H:%M:%S").to_string();
             output.push_str(&format!(
@@ -285,95 +1104,341 @@ impl TypeCmd {
             ));
         }
         
-        print_info(&output);
+        self.output.info(&output);
         Ok(Some(output))
     }
     
-    fn handle_last_command(&mut self) -> Result<Option<String>> {
-        let command_to_execute = match self.history.last() {
-            Some(entry) => {
-                print_gray(&format!("执行历史命令 #{}: {}", entry.id, entry.command));
-                entry.command.clone()
-            }
-            None => {
-                let err = "没有历史命令可执行";
-                print_error(err);
-                return Err(TypeCmdError::InvalidHistoryCommand(err.to_string()));
-            }
-        };
-        
-        self.execute_command(&command_to_execute)
+    pub(crate) fn handle_last_command(&mut self) -> Result<Option<String>> {
+        let entry = self.history.last()
+            .ok_or_else(|| TypeCmdError::InvalidHistoryCommand(tr!("history.nothing")))?;
+        let (id, command) = (entry.id, entry.command.clone());
+        self.replay_history(id, &command)
     }
     
-    fn handle_history_command(&mut self, spec: &str) -> Result<Option<String>> {
-        let command_to_execute = if spec.starts_with('-') {
-            let offset_str = &spec[1..];
+    pub(crate) fn handle_history_command(&mut self, spec: &str) -> Result<Option<String>> {
+        let (id, command) = if let Some(offset_str) = spec.strip_prefix('-') {
             let offset: usize = offset_str.parse()
                 .map_err(|_| TypeCmdError::InvalidHistoryCommand(
-                    format!("无效的偏移量: {}", spec)
+                    tr!("history.bad_offset", spec)
                 ))?;
             
             let entries = self.history.get(None);
             if offset == 0 || offset > entries.len() {
                 return Err(TypeCmdError::InvalidHistoryCommand(
-                    format!("偏移量超出范围 (共{}条)", entries.len())
+                    tr!("history.offset_range", entries.len())
                 ));
             }
-            
             let entry = &entries[entries.len() - offset];
-            print_gray(&format!("执行历史命令 #{}: {}", entry.id, entry.command));
-            entry.command.clone()
+            (entry.id, entry.command.clone())
         } else {
             let id: u64 = spec.parse()
                 .map_err(|_| TypeCmdError::InvalidHistoryCommand(
-                    format!("无效的历史命令ID: {}", spec)
+                    tr!("history.bad_id", spec)
                 ))?;
             
-            match self.history.get_by_id(id) {
-                Some(entry) => {
-                    print_gray(&format!("执行历史命令 #{}: {}", entry.id, entry.command));
-                    entry.command.clone()
-                }
-                None => {
-                    let err = format!("历史命令 #{} 不存在", id);
-                    print_error(&err);
-                    return Err(TypeCmdError::InvalidHistoryCommand(err));
+            let entry = self.history.get_by_id(id)
+                .ok_or_else(|| TypeCmdError::InvalidHistoryCommand(tr!("history.no_id", id)))?;
+            (entry.id, entry.command.clone())
+        };
+        self.replay_history(id, &command)
+    }
+    
+    /// Run history entry `id` again. Entries that refer to history
+    /// themselves are rejected, since running them would replay forever.
+    fn replay_history(&mut self, id: u64, command: &str) -> Result<Option<String>> {
+        if references_history(command)? {
+            return Err(TypeCmdError::InvalidHistoryCommand(tr!("history.replays_history", id)));
+        }
+        if self.replay_depth >= MAX_REPLAY_DEPTH {
+            return Err(TypeCmdError::InvalidHistoryCommand(
+                tr!("history.replay_too_deep", MAX_REPLAY_DEPTH)
+            ));
+        }
+        
+        self.output.muted(&tr!("history.running", id, command));
+        self.replay_depth += 1;
+        let result = self.execute_command(command);
+        self.replay_depth -= 1;
+        result
+    }
+    
+    pub(crate) fn handle_source(&mut self, file: &str) -> Result<Option<String>> {
+        let count = self.run_script(Path::new(file), false)?;
+        let msg = tr!("script.done", file, count);
+        self.status(msg)
+    }
+    
+    /// Run a script file line by line in a new script scope, so variables
+    /// defined with `local` are dropped when it ends.
+    ///
+    /// Blank lines and lines starting with `#` are skipped. Without
+    /// `keep_going` the first failing line aborts the script with a
+    /// `TypeCmdError::Script` error carrying the file name and line number;
+    /// with `keep_going` failures are reported and counted instead.
+    /// Returns the number of executed commands, or the number of failed
+    /// commands when `keep_going` is set.
+    pub fn run_script(&mut self, path: &Path, keep_going: bool) -> Result<usize> {
+        if self.source_depth >= MAX_SOURCE_DEPTH {
+            return Err(TypeCmdError::Other(
+                tr!("script.too_deep", MAX_SOURCE_DEPTH)
+            ));
+        }
+        
+        let content = fs::read_to_string(path).map_err(|source| TypeCmdError::File {
+            file: path.display().to_string(),
+            source,
+        })?;
+        let file = path.display().to_string();
+        
+        // Only an interrupt that arrives while the script runs stops it
+        signal::clear_interrupt();
+        self.source_depth += 1;
+        self.variables.push_scope(ScopeKind::Script);
+        let result = self.run_script_lines(&file, &content, keep_going);
+        self.variables.pop_scope();
+        self.source_depth -= 1;
+        result
+    }
+    
+    fn run_script_lines(&mut self, file: &str, content: &str, keep_going: bool) -> Result<usize> {
+        let mut executed = 0;
+        let mut failed = 0;
+        
+        for (index, line) in content.lines().enumerate() {
+            if self.exit_code.is_some() {
+                break;
+            }
+            if signal::take_interrupt() {
+                return Err(TypeCmdError::Other(tr!("script.interrupted", file)));
+            }
+            
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            
+            executed += 1;
+            if let Err(e) = self.execute_command(line) {
+                // Errors from nested `source` commands already carry their location
+                let err = match e {
+                    TypeCmdError::Script { .. } => e,
+                    e => TypeCmdError::Script {
+                        file: file.to_string(),
+                        line: index + 1,
+                        source: Box::new(e),
+                    },
+                };
+                if !keep_going {
+                    return Err(err);
                 }
+                self.output.error(&format!("{}", err));
+                failed += 1;
             }
-        };
+        }
         
-        self.execute_command(&command_to_execute)
+        Ok(if keep_going { failed } else { executed })
     }
     
     /// Show the command prompt
     pub fn show_prompt(&self) {
+        print_text(&self.prompt());
+    }
+    
+    fn render_prompt(&self, template: &str) -> String {
+        let ctx = PromptContext {
+            version: &self.version,
+            variables: &self.variables,
+            history: &self.history,
+            status: self.last_status,
+        };
+        render(template, &ctx)
+    }
+    
+    /// Build the command prompt text
+    fn prompt(&self) -> String {
+        if let Some(template) = &self.config.prompt {
+            return self.render_prompt(template);
+        }
+        
         let var_count = self.variables.len();
         let hist_count = self.history.count();
         
-        let mut prompt = format!("{}TypeCmd{}{}@{}{}{}{}", PURPLE, RESET, CYAN, RESET, BLUE, self.version, RESET);
+        let mut prompt = format!(
+            "{}{}{}",
+            paint("TypeCmd", Role::Prompt), paint("@", Role::Muted), paint(&self.version, Role::Info)
+        );
 
         if var_count > 0 {
-            prompt.push_str(&format!(" {}[{} vars]{}", CYAN, var_count, RESET));
+            prompt.push_str(&format!(" {}", paint(&format!("[{} vars]", var_count), Role::Info)));
         }
         
         if hist_count > 0 {
-            prompt.push_str(&format!(" {}[{} hist]{}", GREEN, hist_count, RESET));
+            prompt.push_str(&format!(" {}", paint(&format!("[{} hist]", hist_count), Role::Success)));
         }
         
-        prompt.push_str(&format!("\n{}${} ", PURPLE, RESET));
-        print!("{}", prompt);
-        io::stdout().flush().unwrap();
+        prompt.push_str(&format!("\n{} ", paint("$", Role::Prompt)));
+        prompt
     }
     
-    /// Run the TypeCmd REPL
-    pub fn run(&mut self) -> Result<()> {
-        print_info(&format!("TypeCmd {} - 输入 'show help' 查看帮助", self.version));
+    /// Show `prompt` and read a line, using the line editor when stdin is
+    /// a terminal. The right-hand prompt is only shown by the editor.
+    fn read_prompt_line(&mut self, prompt: &str, interactive: bool) -> io::Result<LineInput> {
+        if !interactive {
+            print_text(prompt);
+            return Self::read_input_line();
+        }
         
-        loop {
-            self.show_prompt();
+        // The editor redraws only the last prompt line
+        let (head, last) = prompt.rsplit_once('\n').unwrap_or(("", prompt));
+        if !head.is_empty() {
+            print_line(head);
+        }
+        let plugin_names = self.plugins
+            .iter()
+            .flat_map(|plugin| std::iter::once(&plugin.name).chain(&plugin.aliases))
+            .cloned()
+            .collect();
+        let alias_names = self.aliases.all().keys().map(|name| name.to_string()).collect();
+        let completer = CommandCompleter::new(&self.variables, &self.history)
+            .with_commands(plugin_names)
+            .with_aliases(alias_names);
+        let right = self.config.right_prompt
+            .as_ref()
+            .map(|template| self.render_prompt(template))
+            .unwrap_or_default();
+        self.editor.read_line_with_right(last, &right, &self.history, &completer)
+    }
+    
+    /// Read a command line, continuing on the next line while it ends with
+    /// a backslash. Continuation lines show the continuation prompt.
+    fn read_logical_line(&mut self, interactive: bool) -> io::Result<LineInput> {
+        let prompt = self.prompt();
+        let mut line = match self.read_prompt_line(&prompt, interactive)? {
+            LineInput::Line(line) => line,
+            other => return Ok(other),
+        };
+        
+        while line.ends_with('\\') {
+            line.pop();
+            let prompt = self.render_prompt(&self.config.continuation_prompt);
+            match self.read_prompt_line(&prompt, interactive)? {
+                LineInput::Line(next) => line.push_str(&next),
+                LineInput::Interrupted { .. } => return Ok(LineInput::Interrupted { empty: false }),
+                LineInput::Eof => break,
+            }
+        }
+        Ok(LineInput::Line(line))
+    }
+    
+    /// Run `f` inside a session scope, so `local` variables of a session
+    /// stay out of the global scope
+    fn in_session<T>(&mut self, f: impl FnOnce(&mut Self) -> T) -> T {
+        self.variables.push_scope(ScopeKind::Session);
+        let result = f(self);
+        self.variables.pop_scope();
+        result
+    }
+    
+    /// Execute a command line in its own session scope, as `typecmd -c`
+    /// does. Returns the code given to `exit`, or the exit status.
+    pub fn run_command(&mut self, line: &str) -> Result<i32> {
+        self.in_session(|typecmd| {
+            typecmd.execute_command(line)?;
+            Ok(typecmd.exit_code.unwrap_or(typecmd.last_status))
+        })
+    }
+    
+    /// Execute commands read line by line from a non-interactive source,
+    /// such as piped stdin, in a session scope. Blank lines and `#`
+    /// comments are skipped and errors are reported without stopping.
+    /// Returns the code given to `exit`, or the last exit status.
+    pub fn run_reader<R: BufRead>(&mut self, reader: R) -> Result<i32> {
+        self.in_session(|typecmd| typecmd.read_commands(reader))
+    }
+    
+    fn read_commands<R: BufRead>(&mut self, reader: R) -> Result<i32> {
+        for line in reader.lines() {
+            if self.exit_code.is_some() {
+                break;
+            }
+            
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
             
-            let mut input = String::new();
-            io::stdin().read_line(&mut input)?;
+            if let Err(e) = self.execute_command(line) {
+                self.output.error(&format!("{}", e));
+            }
+        }
+        
+        Ok(self.exit_code.unwrap_or(self.last_status))
+    }
+    
+    /// Read one line from stdin, stopping early on Ctrl-C or end of input
+    fn read_input_line() -> io::Result<LineInput> {
+        signal::take_interrupt();
+        
+        let stdin = io::stdin();
+        let mut stdin = stdin.lock();
+        let mut bytes = Vec::new();
+        let mut byte = [0u8; 1];
+        
+        loop {
+            match stdin.read(&mut byte) {
+                Ok(0) if bytes.is_empty() => return Ok(LineInput::Eof),
+                Ok(0) => break,
+                Ok(_) if byte[0] == b'\n' => break,
+                Ok(_) => bytes.push(byte[0]),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {
+                    if signal::take_interrupt() {
+                        return Ok(LineInput::Interrupted { empty: bytes.is_empty() });
+                    }
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        
+        Ok(LineInput::Line(String::from_utf8_lossy(&bytes).into_owned()))
+    }
+    
+    /// Run the TypeCmd REPL until `exit` is executed, returning its exit
+    /// code. Commands run in a session scope.
+    pub fn run(&mut self) -> Result<i32> {
+        self.in_session(Self::repl)
+    }
+    
+    fn repl(&mut self) -> Result<i32> {
+        self.output.info(&tr!("repl.welcome", self.version));
+        
+        signal::install_sigint_handler();
+        let interactive = LineEditor::is_supported();
+        let mut interrupts = 0;
+        
+        loop {
+            let input = match self.read_logical_line(interactive)? {
+                LineInput::Line(line) => line,
+                LineInput::Interrupted { empty } => {
+                    // Ctrl-C cancels the current line; repeated presses on an
+                    // empty prompt get a hint
+                    interrupts = if empty { interrupts + 1 } else { 0 };
+                    if !interactive {
+                        print_line("");
+                    }
+                    if interrupts > 1 {
+                        self.output.muted(&tr!("repl.exit_hint"));
+                    }
+                    continue;
+                }
+                LineInput::Eof => {
+                    if !interactive {
+                        print_line("");
+                    }
+                    return Ok(self.exit_code.unwrap_or(self.last_status));
+                }
+            };
+            interrupts = 0;
             let input = input.trim();
             
             if input.is_empty() {
@@ -381,28 +1446,163 @@ impl TypeCmd {
             }
             
             if let Err(e) = self.history.add(input) {
-                print_warn(&format!("无法保存历史记录: {}", e));
+                self.output.warn(&tr!("history.save_failed", e));
             }
             
-            let result = if input == "!!" {
-                self.execute_command(input)
-            } else if input.starts_with('!') && input.len() > 1 {
-                let tokens = parse_command(input)?;
-                if !tokens.is_empty() && tokens[0] == "!" && tokens.len() > 1 {
-                    self.execute_command(input)
-                } else {
-                    self.execute_command(input)
-                }
-            } else {
-                self.execute_command(input)
-            };
-            
-            match result {
+            signal::clear_interrupt();
+            match self.execute_command(input) {
                 Ok(_) => {}
                 Err(e) => {
-                    print_error(&format!("{}", e));
+                    self.output.error(&format!("{}", e));
                 }
             }
+            
+            if let Some(code) = self.exit_code {
+                return Ok(code);
+            }
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::{BufferedOutput, OutputKind};
+
+    /// A session that keeps history, aliases and variables in memory
+    fn session() -> (TypeCmd, BufferedOutput) {
+        let dir = env::temp_dir().join(format!("typecmd-test-{}", process::id()));
+        let history = HistoryConfig {
+            history_file: dir.join(".typecmd_history").display().to_string(),
+            persist: false,
+            ..HistoryConfig::default()
+        };
+        let mut typecmd = TypeCmd::with_config(Config { history, ..Config::default() }).unwrap();
+        let output = BufferedOutput::new();
+        typecmd.set_output(Box::new(output.clone()));
+        (typecmd, output)
+    }
+
+    #[test]
+    fn history_replay_refuses_history_references() {
+        let (mut typecmd, _) = session();
+        typecmd.history.add("string x; !!").unwrap();
+        assert!(matches!(typecmd.execute_command("!!"), Err(TypeCmdError::InvalidHistoryCommand(_))));
+        typecmd.history.add("string y | ! -1").unwrap();
+        assert!(matches!(typecmd.execute_command("! 2"), Err(TypeCmdError::InvalidHistoryCommand(_))));
+
+        typecmd.history.add("string ok").unwrap();
+        assert_eq!(typecmd.execute_command("!!").unwrap(), Some("ok".to_string()));
+        assert_eq!(typecmd.execute_command("! -1").unwrap(), Some("ok".to_string()));
+    }
+
+    #[test]
+    fn history_replay_through_alias_terminates() {
+        let (mut typecmd, _) = session();
+        typecmd.execute_command("alias again=!!").unwrap();
+        typecmd.history.add("again").unwrap();
+        assert!(typecmd.execute_command("again").is_err());
+    }
+
+    fn value(typecmd: &TypeCmd, name: &str) -> String {
+        typecmd.variables.get(name).unwrap().to_string()
+    }
+
+    #[test]
+    fn substitution_uses_command_results() {
+        let (mut typecmd, _) = session();
+        typecmd.execute_command("set a x$(calc 1+2)y").unwrap();
+        assert_eq!(value(&typecmd, "a"), "x3y");
+        typecmd.execute_command("set b $(string $(calc 2*3) | upper)").unwrap();
+        assert_eq!(value(&typecmd, "b"), "6");
+        typecmd.execute_command("set c '$(calc 1+1)'").unwrap();
+        assert_eq!(value(&typecmd, "c"), "$(calc 1+1)");
+    }
+
+    #[test]
+    fn substitution_leaves_out_status_messages() {
+        let (mut typecmd, _) = session();
+        typecmd.execute_command("set a [$(set b 1)] [$(exit 4)]").unwrap();
+        assert_eq!(value(&typecmd, "a"), "[] []");
+        assert_eq!(value(&typecmd, "b"), "1");
+        assert_eq!(typecmd.exit_requested(), None);
+        typecmd.execute_command("set c [$(history)]").unwrap();
+        assert_eq!(value(&typecmd, "c"), "[]");
+    }
+
+    #[test]
+    fn missing_script_error_names_the_file() {
+        let (mut typecmd, _) = session();
+        let error = typecmd.run_script(Path::new("missing.tc"), false).unwrap_err();
+        assert!(matches!(&error, TypeCmdError::File { file, .. } if file == "missing.tc"));
+        assert!(error.to_string().starts_with("missing.tc: "));
+    }
+
+    #[test]
+    fn messages_go_to_the_output_sink() {
+        let (mut typecmd, output) = session();
+        typecmd.execute_command("set a 1; get a; get b").unwrap_err();
+        let lines = output.take();
+        let kinds: Vec<_> = lines.iter().map(|line| line.kind).collect();
+        assert_eq!(kinds, [OutputKind::Success, OutputKind::Info]);
+        assert!(lines[1].text.contains('1'));
+
+        typecmd.execute_command("int n = 1 +; string ok").unwrap();
+        assert_eq!(output.lines()[0].kind, OutputKind::Error);
+        assert_eq!(output.lines()[1].text, "ok");
+    }
+
+    #[test]
+    fn run_command_returns_exit_codes() {
+        let (mut typecmd, _) = session();
+        assert_eq!(typecmd.run_command("string a; exit 3; string b").unwrap(), 3);
+        assert_eq!(typecmd.exit_requested(), Some(3));
+
+        let (mut typecmd, _) = session();
+        assert_eq!(typecmd.run_command("string a").unwrap(), 0);
+        assert!(typecmd.run_command("get missing").is_err());
+        assert_eq!(typecmd.last_status(), 1);
+        assert_eq!(typecmd.run_command("get missing || exit").unwrap(), 0);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn failing_programs_set_the_status() {
+        let (mut typecmd, _) = session();
+        assert!(typecmd.run_command("run sh -c 'exit 7'").is_err());
+        assert_eq!(typecmd.last_status(), 7);
+        assert_eq!(typecmd.run_command("run sh -c 'exit 7' || string ok").unwrap(), 0);
+    }
+
+    #[test]
+    fn run_reader_stops_at_exit() {
+        let (mut typecmd, output) = session();
+        let code = typecmd.run_reader(io::Cursor::new("string a\n# note\nexit 5\nstring never\n")).unwrap();
+        assert_eq!(code, 5);
+        assert!(!output.text().contains("never"));
+
+        let (mut typecmd, _) = session();
+        assert_eq!(typecmd.run_reader(io::Cursor::new("string a\nget missing\n")).unwrap(), 1);
+    }
+
+    #[test]
+    fn session_locals_end_with_the_command() {
+        let (mut typecmd, _) = session();
+        typecmd.run_command("set g 1; local t 2; local g 3; set a $g").unwrap();
+        assert_eq!(value(&typecmd, "a"), "3");
+        assert_eq!(value(&typecmd, "g"), "1");
+        assert!(typecmd.variables.get("t").is_none());
+        assert_eq!(typecmd.variables.depth(), 1);
+    }
+
+    #[test]
+    fn readonly_commands_reject_writes() {
+        let (mut typecmd, _) = session();
+        typecmd.execute_command("const k 1; set v 2; readonly v").unwrap();
+        for command in ["set k 2", "local k 2", "rm k", "attr k -readonly", "iset k 3", "set v 3"] {
+            assert!(matches!(typecmd.execute_command(command), Err(TypeCmdError::ReadOnly(_))), "{}", command);
+        }
+        assert_eq!(value(&typecmd, "k"), "1");
+        assert_eq!(value(&typecmd, "v"), "2");
+    }
+}
@@ -0,0 +1,458 @@
+//! Arithmetic and logical expression evaluator
+//!
+//! Supports `+ - * / % **`, parentheses, unary `-` and `!`, comparison
+//! (`== != < <= > >=`) and logical (`&& ||`) operators, number, bool and
+//! quoted string literals, and references to variables by bare name.
+//! `&&` and `||` short-circuit: the right operand is only checked for
+//! syntax when the left one decides the result.
+
+use std::cmp::Ordering;
+
+use crate::error::{TypeCmdError, Result};
+use crate::tr;
+use crate::value::Value;
+use crate::variables::VariableStore;
+
+/// Expression token
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Int(i64),
+    Float(f64),
+    Str(String),
+    Ident(String),
+    Op(&'static str),
+    LParen,
+    RParen,
+}
+
+/// Operators ordered so that longer spellings are matched first
+const OPERATORS: &[&str] = &[
+    "**", "==", "!=", "<=", ">=", "&&", "||",
+    "+", "-", "*", "/", "%", "<", ">", "!",
+];
+
+/// Evaluate an expression, resolving bare names against `vars`
+pub fn evaluate(input: &str, vars: &VariableStore) -> Result<Value> {
+    evaluate_with(input, vars, false)
+}
+
+/// Evaluate an expression in floating-point arithmetic, so that integer
+/// operands give float results, e.g. `7 / 2` is `3.5`
+pub fn evaluate_float(input: &str, vars: &VariableStore) -> Result<Value> {
+    evaluate_with(input, vars, true)
+}
+
+fn evaluate_with(input: &str, vars: &VariableStore, float: bool) -> Result<Value> {
+    let tokens = tokenize(input)?;
+    if tokens.is_empty() {
+        return Err(TypeCmdError::Parse(tr!("expr.empty")));
+    }
+
+    let mut parser = ExprParser { tokens, pos: 0, vars, float, skipping: false };
+    let value = parser.parse_or()?;
+    match parser.peek() {
+        None => Ok(value),
+        Some(tok) => Err(TypeCmdError::Parse(tr!("expr.trailing", format!("{:?}", tok)))),
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let chars: Vec<char> = input.chars().collect();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '(' {
+            tokens.push(Token::LParen);
+            i += 1;
+        } else if c == ')' {
+            tokens.push(Token::RParen);
+            i += 1;
+        } else if c.is_ascii_digit() || (c == '.' && chars.get(i + 1).is_some_and(|n| n.is_ascii_digit())) {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().collect();
+            if text.contains('.') {
+                let num = text.parse::<f64>()
+                    .map_err(|_| TypeCmdError::Parse(tr!("expr.number", text)))?;
+                tokens.push(Token::Float(num));
+            } else {
+                let num = text.parse::<i64>()
+                    .map_err(|_| TypeCmdError::Overflow(tr!("expr.literal_too_large", text)))?;
+                tokens.push(Token::Int(num));
+            }
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push(Token::Ident(chars[start..i].iter().collect()));
+        } else if c == '"' || c == '\'' {
+            let start = i + 1;
+            i = start;
+            while i < chars.len() && chars[i] != c {
+                i += 1;
+            }
+            if i >= chars.len() {
+                return Err(TypeCmdError::Parse(tr!("expr.unclosed_quote")));
+            }
+            tokens.push(Token::Str(chars[start..i].iter().collect()));
+            i += 1;
+        } else {
+            let rest: String = chars[i..chars.len().min(i + 2)].iter().collect();
+            match OPERATORS.iter().find(|op| rest.starts_with(**op)) {
+                Some(op) => {
+                    tokens.push(Token::Op(op));
+                    i += op.len();
+                }
+                None => {
+                    return Err(TypeCmdError::Parse(tr!("expr.invalid_char", c)));
+                }
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Recursive descent parser that evaluates while parsing
+struct ExprParser<'a> {
+    tokens: Vec<Token>,
+    pos: usize,
+    vars: &'a VariableStore,
+    /// Use floating-point arithmetic for integer operands
+    float: bool,
+    /// Parsing an operand whose value cannot change the result, such as the
+    /// right side of `false && ...`; nothing is evaluated
+    skipping: bool,
+}
+
+impl ExprParser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let tok = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        tok
+    }
+
+    /// Consume the next token if it is one of the given operators
+    fn eat_op(&mut self, ops: &[&'static str]) -> Option<&'static str> {
+        match self.peek() {
+            Some(Token::Op(op)) if ops.contains(op) => {
+                let op = *op;
+                self.pos += 1;
+                Some(op)
+            }
+            _ => None,
+        }
+    }
+
+    /// Evaluate `f` unless the current operand is being skipped
+    fn eval(&self, f: impl FnOnce() -> Result<Value>) -> Result<Value> {
+        if self.skipping { Ok(Value::Null) } else { f() }
+    }
+
+    /// Parse an operand with `parse`, skipping its evaluation if `skip`
+    fn parse_skipped(&mut self, skip: bool, parse: fn(&mut Self) -> Result<Value>) -> Result<Value> {
+        let skipping = self.skipping;
+        self.skipping = skipping || skip;
+        let value = parse(self);
+        self.skipping = skipping;
+        value
+    }
+
+    /// Apply an arithmetic operator, in floating point if requested
+    fn binary(&self, op: &str, left: &Value, right: &Value) -> Result<Value> {
+        self.eval(|| match (left, right) {
+            (Value::Int(a), Value::Int(b)) if self.float => {
+                binary(op, &Value::Float(*a as f64), &Value::Float(*b as f64))
+            }
+            _ => binary(op, left, right),
+        })
+    }
+
+    fn parse_or(&mut self) -> Result<Value> {
+        let mut left = self.parse_and()?;
+        while self.eat_op(&["||"]).is_some() {
+            let decided = truthy(&left);
+            let right = self.parse_skipped(decided, Self::parse_and)?;
+            left = Value::Bool(decided || truthy(&right));
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<Value> {
+        let mut left = self.parse_equality()?;
+        while self.eat_op(&["&&"]).is_some() {
+            let decided = !truthy(&left);
+            let right = self.parse_skipped(decided, Self::parse_equality)?;
+            left = Value::Bool(!decided && truthy(&right));
+        }
+        Ok(left)
+    }
+
+    fn parse_equality(&mut self) -> Result<Value> {
+        let mut left = self.parse_relational()?;
+        while let Some(op) = self.eat_op(&["==", "!="]) {
+            let right = self.parse_relational()?;
+            let equal = compare(&left, &right).map(|o| o == Ordering::Equal)
+                .unwrap_or(left == right);
+            left = Value::Bool(if op == "==" { equal } else { !equal });
+        }
+        Ok(left)
+    }
+
+    fn parse_relational(&mut self) -> Result<Value> {
+        let mut left = self.parse_additive()?;
+        while let Some(op) = self.eat_op(&["<", "<=", ">", ">="]) {
+            let right = self.parse_additive()?;
+            if self.skipping {
+                continue;
+            }
+            let ord = compare(&left, &right).ok_or_else(|| TypeCmdError::TypeMismatch(
+                tr!("expr.compare", left.type_name(), right.type_name())
+            ))?;
+            left = Value::Bool(match op {
+                "<" => ord == Ordering::Less,
+                "<=" => ord != Ordering::Greater,
+                ">" => ord == Ordering::Greater,
+                _ => ord != Ordering::Less,
+            });
+        }
+        Ok(left)
+    }
+
+    fn parse_additive(&mut self) -> Result<Value> {
+        let mut left = self.parse_multiplicative()?;
+        while let Some(op) = self.eat_op(&["+", "-"]) {
+            let right = self.parse_multiplicative()?;
+            left = self.binary(op, &left, &right)?;
+        }
+        Ok(left)
+    }
+
+    fn parse_multiplicative(&mut self) -> Result<Value> {
+        let mut left = self.parse_unary()?;
+        while let Some(op) = self.eat_op(&["*", "/", "%"]) {
+            let right = self.parse_unary()?;
+            left = self.binary(op, &left, &right)?;
+        }
+        Ok(left)
+    }
+
+    fn parse_unary(&mut self) -> Result<Value> {
+        match self.eat_op(&["-", "!"]) {
+            Some("-") => {
+                let value = self.parse_unary()?;
+                self.eval(|| match value {
+                    Value::Int(n) => n.checked_neg().map(Value::Int)
+                        .ok_or_else(|| TypeCmdError::Overflow(format!("-({})", n))),
+                    Value::Float(x) => Ok(Value::Float(-x)),
+                    other => Err(TypeCmdError::TypeMismatch(
+                        tr!("expr.negate", other.type_name())
+                    )),
+                })
+            }
+            Some(_) => {
+                let value = self.parse_unary()?;
+                Ok(Value::Bool(!truthy(&value)))
+            }
+            None => self.parse_power(),
+        }
+    }
+
+    fn parse_power(&mut self) -> Result<Value> {
+        let base = self.parse_primary()?;
+        if self.eat_op(&["**"]).is_some() {
+            // Right associative: 2 ** 3 ** 2 == 2 ** 9
+            let exponent = self.parse_unary()?;
+            return self.binary("**", &base, &exponent);
+        }
+        Ok(base)
+    }
+
+    fn parse_primary(&mut self) -> Result<Value> {
+        match self.next() {
+            Some(Token::Int(n)) => Ok(Value::Int(n)),
+            Some(Token::Float(x)) => Ok(Value::Float(x)),
+            Some(Token::Str(s)) => Ok(Value::Str(s)),
+            Some(Token::Ident(name)) => match name.as_str() {
+                "true" => Ok(Value::Bool(true)),
+                "false" => Ok(Value::Bool(false)),
+                "null" => Ok(Value::Null),
+                _ => self.eval(|| self.vars.get(&name).cloned()
+                    .ok_or(TypeCmdError::UndefinedVariable(name))),
+            },
+            Some(Token::LParen) => {
+                let value = self.parse_or()?;
+                match self.next() {
+                    Some(Token::RParen) => Ok(value),
+                    _ => Err(TypeCmdError::Parse(tr!("expr.missing_paren"))),
+                }
+            }
+            Some(tok) => Err(TypeCmdError::Parse(tr!("expr.unexpected", format!("{:?}", tok)))),
+            None => Err(TypeCmdError::Parse(tr!("expr.unexpected_end"))),
+        }
+    }
+}
+
+/// Truthiness of a value in logical expressions
+fn truthy(value: &Value) -> bool {
+    match value {
+        Value::Bool(b) => *b,
+        Value::Int(n) => *n != 0,
+        Value::Float(x) => *x != 0.0,
+        Value::Str(s) => !s.is_empty(),
+        Value::Null => false,
+    }
+}
+
+/// Compare two values of compatible types
+fn compare(left: &Value, right: &Value) -> Option<Ordering> {
+    match (left, right) {
+        (Value::Int(a), Value::Int(b)) => Some(a.cmp(b)),
+        (Value::Str(a), Value::Str(b)) => Some(a.cmp(b)),
+        (Value::Bool(a), Value::Bool(b)) => Some(a.cmp(b)),
+        _ => match (as_float(left), as_float(right)) {
+            (Some(a), Some(b)) => a.partial_cmp(&b),
+            _ => None,
+        },
+    }
+}
+
+fn as_float(value: &Value) -> Option<f64> {
+    match value {
+        Value::Int(n) => Some(*n as f64),
+        Value::Float(x) => Some(*x),
+        _ => None,
+    }
+}
+
+/// Apply an arithmetic operator
+fn binary(op: &str, left: &Value, right: &Value) -> Result<Value> {
+    if let (Value::Int(a), Value::Int(b)) = (left, right) {
+        return int_binary(op, *a, *b);
+    }
+
+    if op == "+" && (matches!(left, Value::Str(_)) || matches!(right, Value::Str(_))) {
+        return Ok(Value::Str(format!("{}{}", left, right)));
+    }
+
+    let (a, b) = match (as_float(left), as_float(right)) {
+        (Some(a), Some(b)) => (a, b),
+        _ => {
+            return Err(TypeCmdError::TypeMismatch(tr!(
+                "expr.operator", left.type_name(), right.type_name(), op
+            )));
+        }
+    };
+
+    let result = match op {
+        "+" => a + b,
+        "-" => a - b,
+        "*" => a * b,
+        "/" | "%" if b == 0.0 => return Err(TypeCmdError::DivisionByZero),
+        "/" => a / b,
+        "%" => a % b,
+        _ => a.powf(b),
+    };
+
+    if result.is_finite() {
+        Ok(Value::Float(result))
+    } else {
+        Err(TypeCmdError::Overflow(format!("{} {} {}", a, op, b)))
+    }
+}
+
+/// Apply an arithmetic operator to two integers with overflow checks
+fn int_binary(op: &str, a: i64, b: i64) -> Result<Value> {
+    let result = match op {
+        "+" => a.checked_add(b),
+        "-" => a.checked_sub(b),
+        "*" => a.checked_mul(b),
+        "/" | "%" if b == 0 => return Err(TypeCmdError::DivisionByZero),
+        "/" => a.checked_div(b),
+        "%" => a.checked_rem(b),
+        _ => {
+            if b < 0 {
+                return binary(op, &Value::Float(a as f64), &Value::Float(b as f64));
+            }
+            u32::try_from(b).ok().and_then(|exp| a.checked_pow(exp))
+        }
+    };
+
+    result
+        .map(Value::Int)
+        .ok_or_else(|| TypeCmdError::Overflow(format!("{} {} {}", a, op, b)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(input: &str) -> Result<Value> {
+        evaluate(input, &VariableStore::new())
+    }
+
+    #[test]
+    fn precedence() {
+        assert_eq!(eval("2 + 3 * 4").unwrap(), Value::Int(14));
+        assert_eq!(eval("(2 + 3) * 4").unwrap(), Value::Int(20));
+        assert_eq!(eval("10 - 4 - 3").unwrap(), Value::Int(3));
+        assert_eq!(eval("2 ** 3 ** 2").unwrap(), Value::Int(512));
+        assert_eq!(eval("-2 ** 2").unwrap(), Value::Int(-4));
+        assert_eq!(eval("1 + 2 == 3 && 2 < 3").unwrap(), Value::Bool(true));
+        assert_eq!(eval("true || false && false").unwrap(), Value::Bool(true));
+        assert_eq!(eval("!(1 > 2)").unwrap(), Value::Bool(true));
+    }
+
+    #[test]
+    fn overflow() {
+        assert!(matches!(eval("9223372036854775807 + 1"), Err(TypeCmdError::Overflow(_))));
+        assert!(matches!(eval("2 ** 64"), Err(TypeCmdError::Overflow(_))));
+        assert!(matches!(eval("99999999999999999999"), Err(TypeCmdError::Overflow(_))));
+        assert!(matches!(eval("-(-9223372036854775807 - 1)"), Err(TypeCmdError::Overflow(_))));
+    }
+
+    #[test]
+    fn division_by_zero() {
+        assert!(matches!(eval("1 / 0"), Err(TypeCmdError::DivisionByZero)));
+        assert!(matches!(eval("1 % 0"), Err(TypeCmdError::DivisionByZero)));
+        assert!(matches!(eval("1.5 / 0"), Err(TypeCmdError::DivisionByZero)));
+    }
+
+    #[test]
+    fn logical_operators_short_circuit() {
+        assert_eq!(eval("false && (1 / 0)").unwrap(), Value::Bool(false));
+        assert_eq!(eval("1 == 1 || nosuch").unwrap(), Value::Bool(true));
+        assert!(matches!(eval("true && nosuch"), Err(TypeCmdError::UndefinedVariable(_))));
+        assert!(matches!(eval("false && (1 +"), Err(TypeCmdError::Parse(_))));
+    }
+
+    #[test]
+    fn float_mode() {
+        let vars = VariableStore::new();
+        assert_eq!(evaluate("7 / 2", &vars).unwrap(), Value::Int(3));
+        assert_eq!(evaluate_float("7 / 2", &vars).unwrap(), Value::Float(3.5));
+    }
+
+    #[test]
+    fn strings_and_variables() {
+        let mut vars = VariableStore::new();
+        vars.set("x".to_string(), Value::Int(5)).unwrap();
+        assert_eq!(evaluate("x * 2", &vars).unwrap(), Value::Int(10));
+        assert_eq!(eval("'a' + 1").unwrap(), Value::Str("a1".to_string()));
+        assert_eq!(eval("\"a b\" == 'a b'").unwrap(), Value::Bool(true));
+        assert!(matches!(eval("'open"), Err(TypeCmdError::Parse(_))));
+        assert!(matches!(eval("'a' < 1"), Err(TypeCmdError::TypeMismatch(_))));
+    }
+}
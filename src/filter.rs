@@ -0,0 +1,96 @@
+//! Text filters for pipelines
+//!
+//! Filters transform the output of the previous command in a pipeline,
+//! e.g. `history | grep set | count`. Line-based filters treat the input as
+//! `\n`-separated lines.
+
+/// Lines kept by `head` and `tail` without a count
+pub const DEFAULT_LINES: usize = 10;
+
+/// A filter applied to piped input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Filter {
+    /// Convert to upper case
+    Upper,
+    /// Convert to lower case
+    Lower,
+    /// Remove leading and trailing whitespace
+    Trim,
+    /// Number every line
+    Lines,
+    /// Keep the lines containing a text
+    Grep(String),
+    /// Count the lines
+    Count,
+    /// Keep the first n lines
+    Head(usize),
+    /// Keep the last n lines
+    Tail(usize),
+}
+
+impl Filter {
+    /// Command name of the filter
+    pub fn name(&self) -> &'static str {
+        match self {
+            Filter::Upper => "upper",
+            Filter::Lower => "lower",
+            Filter::Trim => "trim",
+            Filter::Lines => "lines",
+            Filter::Grep(_) => "grep",
+            Filter::Count => "count",
+            Filter::Head(_) => "head",
+            Filter::Tail(_) => "tail",
+        }
+    }
+
+    /// Apply the filter to `input`
+    pub fn apply(&self, input: &str) -> String {
+        match self {
+            Filter::Upper => input.to_uppercase(),
+            Filter::Lower => input.to_lowercase(),
+            Filter::Trim => input.trim().to_string(),
+            Filter::Lines => input
+                .lines()
+                .enumerate()
+                .map(|(index, line)| format!("{:>6}  {}", index + 1, line))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            Filter::Grep(text) => input
+                .lines()
+                .filter(|line| line.contains(text.as_str()))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            Filter::Count => input.lines().count().to_string(),
+            Filter::Head(count) => input.lines().take(*count).collect::<Vec<_>>().join("\n"),
+            Filter::Tail(count) => {
+                let lines: Vec<_> = input.lines().collect();
+                lines[lines.len().saturating_sub(*count)..].join("\n")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUT: &str = "alpha\nbeta\ngamma\n";
+
+    #[test]
+    fn line_filters() {
+        assert_eq!(Filter::Grep("a".to_string()).apply("alpha\nbeta\nxyz"), "alpha\nbeta");
+        assert_eq!(Filter::Count.apply(INPUT), "3");
+        assert_eq!(Filter::Count.apply(""), "0");
+        assert_eq!(Filter::Head(2).apply(INPUT), "alpha\nbeta");
+        assert_eq!(Filter::Tail(2).apply(INPUT), "beta\ngamma");
+        assert_eq!(Filter::Tail(10).apply(INPUT), "alpha\nbeta\ngamma");
+        assert_eq!(Filter::Lines.apply("a\nb"), "     1  a\n     2  b");
+    }
+
+    #[test]
+    fn text_filters() {
+        assert_eq!(Filter::Upper.apply("Hi 你好"), "HI 你好");
+        assert_eq!(Filter::Lower.apply("Hi"), "hi");
+        assert_eq!(Filter::Trim.apply("  hi \n"), "hi");
+    }
+}
@@ -1,12 +1,12 @@
 use std::collections::VecDeque;
 use std::env;
 use std::fs::{self, File, OpenOptions};
-use std::io::{self, BufRead, BufReader, Write};
-use std::path::PathBuf;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
 use chrono::{DateTime, Local};
 use serde::{Deserialize, Serialize};
 
-use crate::error::{TypeCmdError, Result};
+use crate::error::Result;
 
 /// History entry structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,6 +22,8 @@ pub struct HistoryConfig {
     pub max_history_size: usize,
     pub history_file: String,
     pub version: String,
+    /// Load history from and save it to `history_file`
+    pub persist: bool,
 }
 
 impl Default for HistoryConfig {
@@ -30,6 +32,7 @@ impl Default for HistoryConfig {
             max_history_size: 1000,
             history_file: ".typecmd_history".to_string(),
             version: "0.4.0".to_string(),
+            persist: true,
         }
     }
 }
@@ -59,17 +62,24 @@ impl HistoryManager {
         Ok(manager)
     }
     
-    /// Get the path to the history file
-    fn history_path(&self) -> PathBuf {
+    /// Get the path to the history file.
+    ///
+    /// A bare file name is resolved in the home directory; absolute paths
+    /// and paths with a directory component are used as given.
+    pub fn history_path(&self) -> PathBuf {
+        let file = Path::new(&self.config.history_file);
+        if file.is_absolute() || file.components().count() > 1 {
+            return file.to_path_buf();
+        }
         let home = env::var("HOME").unwrap_or_else(|_| ".".to_string());
-        PathBuf::from(home).join(&self.config.history_file)
+        PathBuf::from(home).join(file)
     }
     
     /// Load history from file
     fn load_history(&mut self) -> Result<()> {
         let path = self.history_path();
         
-        if !path.exists() {
+        if !self.config.persist || !path.exists() {
             return Ok(());
         }
         
@@ -83,20 +93,21 @@ impl HistoryManager {
             }
             
             let parts: Vec<&str> = line.splitn(3, '|').collect();
-            if parts.len() == 3 {
-                if let (Ok(id), Ok(timestamp)) = (
-                    parts[0].parse::<u64>(),
-                    parts[1].parse::<DateTime<Local>>()
-                ) {
-                    let entry = HistoryEntry {
-                        id,
-                        command: parts[2].to_string(),
-                        timestamp,
-                    };
-                    
-                    self.entries.push_back(entry);
-                    self.next_id = self.next_id.max(id + 1);
-                }
+            if parts.len() != 3 {
+                continue;
+            }
+            if let (Ok(id), Ok(timestamp)) = (
+                parts[0].parse::<u64>(),
+                parts[1].parse::<DateTime<Local>>()
+            ) {
+                let entry = HistoryEntry {
+                    id,
+                    command: parts[2].to_string(),
+                    timestamp,
+                };
+                
+                self.entries.push_back(entry);
+                self.next_id = self.next_id.max(id + 1);
             }
         }
         
@@ -110,6 +121,10 @@ impl HistoryManager {
     
     /// Save history to file
     fn save_history(&self) -> Result<()> {
+        if !self.config.persist {
+            return Ok(());
+        }
+        
         let path = self.history_path();
         let mut file = OpenOptions::new()
             .create(true)
@@ -185,7 +200,9 @@ impl HistoryManager {
     pub fn clear(&mut self) -> Result<()> {
         self.entries.clear();
         self.next_id = 1;
-        let _ = fs::remove_file(self.history_path());
+        if self.config.persist {
+            let _ = fs::remove_file(self.history_path());
+        }
         Ok(())
     }
     
@@ -194,6 +211,11 @@ impl HistoryManager {
         self.entries.back()
     }
     
+    /// Id that the next added entry will get
+    pub fn next_id(&self) -> u64 {
+        self.next_id
+    }
+    
     /// Get the count of history entries
     pub fn count(&self) -> usize {
         self.entries.len()
@@ -0,0 +1,625 @@
+//! Message catalog
+//!
+//! User-facing text is looked up by message id in the zh-CN or en-US
+//! bundle of the current language. Messages use `{}` placeholders that
+//! `tr!` fills in order. Ids missing from a bundle fall back to zh-CN.
+
+use std::env;
+use std::fmt::Display;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Supported message languages
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lang {
+    ZhCn,
+    EnUs,
+}
+
+/// Language tags accepted by `--lang` and `config lang`
+pub const LANG_TAGS: &[&str] = &["zh-CN", "en-US"];
+
+impl Lang {
+    /// Parse a language tag or locale such as `en`, `zh-CN` or
+    /// `en_US.UTF-8`
+    pub fn parse(tag: &str) -> Option<Lang> {
+        let tag = tag.to_lowercase();
+        let language = tag.split(['-', '_', '.']).next().unwrap_or("");
+        match language {
+            "zh" => Some(Lang::ZhCn),
+            "en" => Some(Lang::EnUs),
+            _ => None,
+        }
+    }
+
+    /// Language from `LC_ALL`, `LC_MESSAGES` or `LANG`, zh-CN if none of
+    /// them names a supported language
+    pub fn from_env() -> Lang {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|name| env::var(name).ok())
+            .find(|value| !value.is_empty())
+            .and_then(|value| Lang::parse(&value))
+            .unwrap_or(Lang::ZhCn)
+    }
+
+    /// Canonical tag, e.g. `zh-CN`
+    pub fn tag(self) -> &'static str {
+        match self {
+            Lang::ZhCn => "zh-CN",
+            Lang::EnUs => "en-US",
+        }
+    }
+}
+
+static LANG: AtomicU8 = AtomicU8::new(Lang::ZhCn as u8);
+
+/// Set the language of all messages
+pub fn set_lang(lang: Lang) {
+    LANG.store(lang as u8, Ordering::Relaxed);
+}
+
+/// Current message language
+pub fn lang() -> Lang {
+    match LANG.load(Ordering::Relaxed) {
+        1 => Lang::EnUs,
+        _ => Lang::ZhCn,
+    }
+}
+
+fn bundle(lang: Lang) -> &'static [(&'static str, &'static str)] {
+    match lang {
+        Lang::ZhCn => ZH_CN,
+        Lang::EnUs => EN_US,
+    }
+}
+
+/// Message text for `id` in the current language
+pub fn text(id: &'static str) -> &'static str {
+    let find = |lang| bundle(lang).iter().find(|(key, _)| *key == id).map(|(_, text)| *text);
+    find(lang()).or_else(|| find(Lang::ZhCn)).unwrap_or(id)
+}
+
+/// Message text for `id` with its `{}` placeholders replaced by `args`
+pub fn message(id: &'static str, args: &[&dyn Display]) -> String {
+    let mut args = args.iter();
+    let mut out = String::new();
+    let mut rest = text(id);
+    while let Some(pos) = rest.find("{}") {
+        out.push_str(&rest[..pos]);
+        match args.next() {
+            Some(arg) => out.push_str(&arg.to_string()),
+            None => out.push_str("{}"),
+        }
+        rest = &rest[pos + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Look up a message by id and fill in its placeholders:
+/// `tr!("error.command_not_found", name)`
+#[macro_export]
+macro_rules! tr {
+    ($id:expr) => {
+        $crate::i18n::message($id, &[])
+    };
+    ($id:expr, $($arg:expr),+ $(,)?) => {
+        $crate::i18n::message($id, &[$(&$arg as &dyn ::std::fmt::Display),+])
+    };
+}
+
+const ZH_CN: &[(&str, &str)] = &[
+    ("error.io", "I/O错误: {}"),
+    ("error.parse", "解析错误: {}"),
+    ("error.command_not_found", "未找到命令: {}"),
+    ("error.command_exists", "命令已存在: {}"),
+    ("error.insufficient_args", "参数不足: {}"),
+    ("error.undefined_variable", "变量未定义: {}"),
+    ("error.invalid_history_command", "无效的历史命令: {}"),
+    ("error.division_by_zero", "除数为零"),
+    ("error.overflow", "数值溢出: {}"),
+    ("error.type_mismatch", "类型不匹配: {}"),
+    ("error.readonly", "只读变量不能修改或删除: {}"),
+    ("error.other", "其他错误: {}"),
+    ("parse.unclosed_quote", "未闭合的引号"),
+    ("parse.chain_missing_tail", "命令链末尾缺少命令"),
+    ("parse.chain_missing_command", "{} 前缺少命令"),
+    ("parse.unclosed_variable", "未闭合的变量引用 ${"),
+    ("parse.empty_variable", "空的变量引用 ${}"),
+    ("parse.empty_command", "空命令"),
+    ("parse.history_limit", "无效的历史记录限制"),
+    ("parse.unknown_show", "未知的show子命令: {}"),
+    ("parse.exit_code", "无效的退出码"),
+    ("parse.bool", "无效的布尔值, 应为 true 或 false"),
+    ("parse.clear_target", "clear命令参数应为: vars 或 history"),
+    ("parse.vars_target", "save/load命令参数应为: vars [文件]"),
+    ("parse.export_target", "export命令参数应为: json [文件] 或 变量名..."),
+    ("parse.export_json_name", "不能用export导出名为 {} 的变量, export json 表示导出JSON; 可用 attr {} +exported"),
+    ("parse.import_target", "import命令参数应为: json <文件> [--merge|--replace] 或 env <名称...|--all>"),
+    ("parse.unknown_option", "未知选项: {}"),
+    ("parse.unclosed_substitution", "命令替换 $( 缺少右括号"),
+    ("parse.pipe_missing_command", "管道符 | 两侧需要命令"),
+    ("parse.line_count", "行数应为非负整数: {}"),
+    ("parse.attr_flag", "属性应为 +名称 或 -名称 (readonly, exported, integer, hidden): {}"),
+    ("parse.history_arg", "无效的历史命令参数"),
+    ("args.at_least_two", "{}命令需要至少2个参数"),
+    ("args.copy_extra", "copy命令参数过多, 忽略剩余参数"),
+    ("args.source_file", "source命令需要文件名"),
+    ("args.history_exec", "历史命令需要参数"),
+    ("args.alias_expansion", "alias命令需要展开内容: alias 名称=命令"),
+    ("args.unalias_name", "unalias命令需要别名"),
+    ("args.config_value", "config命令需要配置项和值"),
+    ("args.variable_name", "{}命令需要变量名"),
+    ("args.calc_expr", "calc命令需要表达式"),
+    ("args.search_keyword", "搜索需要关键词"),
+    ("args.import_file", "import json 需要文件名"),
+    ("args.import_env", "import env 需要变量名或 --all"),
+    ("args.run_program", "run命令需要程序名"),
+    ("args.grep_text", "grep命令需要搜索文本"),
+    ("expr.empty", "空表达式"),
+    ("expr.trailing", "表达式中有多余的内容: {}"),
+    ("expr.number", "无效的数字: {}"),
+    ("expr.literal_too_large", "整数字面量过大: {}"),
+    ("expr.unclosed_quote", "表达式中有未闭合的引号"),
+    ("expr.invalid_char", "表达式中有无效字符: {}"),
+    ("expr.compare", "无法比较 {} 和 {}"),
+    ("expr.negate", "无法对 {} 取负"),
+    ("expr.missing_paren", "表达式缺少右括号"),
+    ("expr.unexpected", "表达式中有意外的符号: {}"),
+    ("expr.unexpected_end", "表达式意外结束"),
+    ("expr.operator", "无法对 {} 和 {} 使用运算符 {}"),
+    ("expr.expected", "表达式结果为 {}, 需要 {}"),
+    ("alias.invalid", "无效的别名: {}"),
+    ("alias.none", "没有定义的别名"),
+    ("alias.header", "已定义的别名 (共{}个):"),
+    ("alias.not_found", "别名不存在: {}"),
+    ("alias.set", "别名 \"{}\" 已设置为 '{}'"),
+    ("alias.removed", "已删除别名: {}"),
+    ("prefix.error", "错误: {}"),
+    ("prefix.warn", "警告: {}"),
+    ("config.history_size", "无效的历史记录大小: {}"),
+    ("config.unknown_theme", "未知的主题: {}"),
+    ("config.unknown_key", "未知的配置项: {}"),
+    ("config.bool", "无效的布尔值: {}"),
+    ("config.unknown_lang", "未知的语言: {}"),
+    ("config.no_file", "未加载配置文件"),
+    ("config.header", "当前配置 ({}):"),
+    ("config.next_start", "{} 将在下次启动时生效"),
+    ("config.set", "配置项 {} 已设置为 {}"),
+    ("config.autosave_failed", "自动保存变量失败: {}"),
+    ("theme.invalid_color", "无效的颜色: {}"),
+    ("theme.switched", "已切换到主题: {}"),
+    ("theme.available", "可用的主题:"),
+    ("theme.styles", "当前主题的样式:"),
+    ("plugin.summary", "自定义命令"),
+    ("help.usage", "用法: {} {}"),
+    ("help.aliases", "别名: {}"),
+    ("help.chain.always", "依次执行a和b"),
+    ("help.chain.and", "a成功后执行b"),
+    ("help.chain.or", "a失败后执行b"),
+    ("help.history.last", "执行上一条命令"),
+    ("help.history.id", "执行历史第n条命令"),
+    ("help.history.offset", "执行历史倒数第n条命令"),
+    ("help.history.list", "显示所有历史命令"),
+    ("help.history.recent", "显示最近n条历史命令"),
+    ("help.history.search", "搜索包含str的历史命令"),
+    ("help.history.clear", "清除所有历史记录"),
+    ("help.expr.arith", "算术运算, 如 iset total (a + b * 2)"),
+    ("help.expr.logic", "比较与逻辑运算"),
+    ("help.variables.expand", "在参数中展开变量的值 (单引号内不展开, \\$ 表示字面$)"),
+    ("help.variables.env", "展开环境变量的值"),
+    ("help.variables.capture", "执行命令并替换为其输出"),
+    ("help.pipe.pass", "将a的输出作为b的输入"),
+    ("help.pipe.set", "将管道的结果保存到变量"),
+    ("help.section.builtin", "基础命令"),
+    ("help.section.plugins", "扩展命令"),
+    ("help.section.chain", "命令链"),
+    ("help.section.history", "历史命令使用"),
+    ("help.section.expr", "表达式"),
+    ("help.section.variables", "变量引用"),
+    ("help.section.pipe", "管道"),
+    ("help.title", "TypeCmd 命令行模拟器"),
+    ("help.header", "版本: {}\n历史记录: {} 条命令"),
+    ("main.see_help", "使用 typecmd --help 查看用法"),
+    ("main.config_load", "无法加载配置文件: {}"),
+    ("cmd.show.summary", "显示信息"),
+    ("cmd.show.usage", "[help|ver|vars [--scope]|history|license|aliases|config]"),
+    ("cmd.help.summary", "显示帮助或命令用法"),
+    ("cmd.help.usage", "[命令]"),
+    ("cmd.exit.summary", "退出程序"),
+    ("cmd.exit.usage", "[退出码]"),
+    ("cmd.set.summary", "设置变量"),
+    ("cmd.set.usage", "<变量名> <值>"),
+    ("cmd.iset.summary", "设置整数变量"),
+    ("cmd.iset.usage", "<变量名> <值|表达式>"),
+    ("cmd.fset.summary", "设置浮点变量"),
+    ("cmd.fset.usage", "<变量名> <值|表达式>"),
+    ("cmd.bset.summary", "设置布尔变量"),
+    ("cmd.bset.usage", "<变量名> <true|false>"),
+    ("cmd.local.summary", "在当前作用域定义变量"),
+    ("cmd.local.usage", "<变量名> <值>"),
+    ("cmd.const.summary", "定义只读常量"),
+    ("cmd.const.usage", "<变量名> <值>"),
+    ("cmd.readonly.summary", "将变量设为只读"),
+    ("cmd.readonly.usage", "<变量名> [值]"),
+    ("cmd.attr.summary", "查看或修改变量属性"),
+    ("cmd.attr.usage", "<变量名> [+属性|-属性 ...]"),
+    ("cmd.get.summary", "获取变量"),
+    ("cmd.get.usage", "<变量名>"),
+    ("cmd.copy.summary", "复制变量"),
+    ("cmd.copy.usage", "<新变量名> <旧变量名>"),
+    ("cmd.string.summary", "字符串输出"),
+    ("cmd.string.usage", "<文本>"),
+    ("cmd.int.summary", "整数计算"),
+    ("cmd.int.usage", "<表达式>"),
+    ("cmd.calc.summary", "计算表达式"),
+    ("cmd.calc.usage", "<表达式>"),
+    ("cmd.list.summary", "列出所有变量"),
+    ("cmd.rm.summary", "删除变量"),
+    ("cmd.rm.usage", "<变量名>"),
+    ("cmd.clear.summary", "清空所有变量或历史"),
+    ("cmd.clear.usage", "[vars|history]"),
+    ("cmd.history.summary", "显示历史命令"),
+    ("cmd.history.usage", "[n|search <关键词>|clear]"),
+    ("cmd.version.summary", "等同于show ver"),
+    ("cmd.source.summary", "执行脚本文件"),
+    ("cmd.source.usage", "<文件>"),
+    ("cmd.alias.summary", "定义或列出别名"),
+    ("cmd.alias.usage", "[名称=命令]"),
+    ("cmd.unalias.summary", "删除别名"),
+    ("cmd.unalias.usage", "<名称>"),
+    ("cmd.config.summary", "修改或显示配置"),
+    ("cmd.config.usage", "[配置项 值]"),
+    ("cmd.theme.summary", "切换或列出颜色主题"),
+    ("cmd.theme.usage", "[dark|light|mono]"),
+    ("cmd.save.summary", "保存变量到文件"),
+    ("cmd.save.usage", "vars [文件]"),
+    ("cmd.load.summary", "从文件加载变量"),
+    ("cmd.load.usage", "vars [文件]"),
+    ("cmd.export.summary", "将变量导出为JSON, 或标记为传给子进程的环境变量"),
+    ("cmd.export.usage", "json [文件] | <变量名...>"),
+    ("cmd.import.summary", "从JSON文件或环境变量导入变量"),
+    ("cmd.import.usage", "json <文件> [--merge|--replace] | env <名称...|--all>"),
+    ("cmd.env.summary", "列出环境变量或显示其中一个"),
+    ("cmd.env.usage", "[名称]"),
+    ("cmd.run.summary", "运行外部程序"),
+    ("cmd.run.usage", "<程序> [参数...]"),
+    ("cmd.upper.summary", "将输入转为大写"),
+    ("cmd.lower.summary", "将输入转为小写"),
+    ("cmd.trim.summary", "去掉输入首尾的空白"),
+    ("cmd.lines.summary", "为输入的每一行编号"),
+    ("cmd.grep.summary", "保留包含指定文本的行"),
+    ("cmd.grep.usage", "<文本>"),
+    ("cmd.count.summary", "统计输入的行数"),
+    ("cmd.head.summary", "保留输入的前n行 (默认10行)"),
+    ("cmd.head.usage", "[n]"),
+    ("cmd.tail.summary", "保留输入的最后n行 (默认10行)"),
+    ("cmd.tail.usage", "[n]"),
+    ("cmd.last.summary", "执行上一条命令"),
+    ("cmd.history_exec.summary", "执行历史第n条或倒数第n条命令"),
+    ("cmd.history_exec.usage", "<n|-n>"),
+    ("vars.none", "没有定义的变量"),
+    ("vars.header", "已定义的变量 (共{}个):"),
+    ("vars.set", "变量 \"{}\" 已设置为 \"{}\" ({})"),
+    ("vars.local_set", "局部变量 \"{}\" 已设置为 \"{}\" ({})"),
+    ("vars.const_set", "常量 \"{}\" 已设置为 \"{}\" ({})"),
+    ("vars.readonly_set", "变量 \"{}\" 已设为只读"),
+    ("vars.attributes", "变量 {} 的属性: {}"),
+    ("vars.no_attributes", "无"),
+    ("vars.integer_only", "整数变量 {} 不能保存 {} 类型的值"),
+    ("vars.readonly_kept", "保留了 {} 个只读变量"),
+    ("vars.exported_env", "已导出变量: {}"),
+    ("vars.value", "变量 {} 的值为: {} ({})"),
+    ("vars.copied", "变量 \"{}\" 已设置为 变量\"{}\"的值 \"{}\" ({})"),
+    ("vars.deleted", "已删除变量: {}"),
+    ("vars.not_found", "变量不存在: {}"),
+    ("vars.cleared", "已清除所有变量 (共{}个)"),
+    ("vars.saved", "已保存 {} 个变量到 {}"),
+    ("vars.loaded", "已从 {} 加载 {} 个变量"),
+    ("vars.skipped", "{}: 跳过 {} 行无效内容"),
+    ("vars.exported", "已导出 {} 个变量到 {}"),
+    ("vars.imported", "已从 {} 导入 {} 个变量"),
+    ("vars.replaced", "已用 {} 中的 {} 个变量替换所有变量"),
+    ("vars.invalid_json", "JSON格式错误: {}"),
+    ("vars.json_no_attributes", "JSON不保存变量属性, 已忽略{}个变量的属性"),
+    ("vars.scope_header", "{}作用域 (共{}个):"),
+    ("vars.scope.global", "全局"),
+    ("vars.scope.session", "会话"),
+    ("vars.scope.script", "脚本"),
+    ("vars.scope.block", "块"),
+    ("env.header", "环境变量 (共{}个):"),
+    ("env.value", "环境变量 {} 的值为: {}"),
+    ("env.not_set", "环境变量未设置: {}"),
+    ("env.imported", "已从环境导入 {} 个变量"),
+    ("run.failed", "无法运行 {}: {}"),
+    ("run.exit_status", "{} 退出状态为 {}"),
+    ("pipe.no_input", "{}命令需要管道输入, 例如: history | {}"),
+    ("history.empty", "历史记录为空"),
+    ("history.recent", "最近{}条"),
+    ("history.all", "所有"),
+    ("history.header", "历史命令 ({}):"),
+    ("history.hint", "使用 !<编号> 执行历史命令"),
+    ("history.cleared_all", "已清除所有历史记录"),
+    ("history.cleared", "历史记录已清除"),
+    ("history.no_match", "没有找到包含 \"{}\" 的历史命令"),
+    ("history.search_header", "搜索 \"{}\" 的结果 ({}条):"),
+    ("history.running", "执行历史命令 #{}: {}"),
+    ("history.nothing", "没有历史命令可执行"),
+    ("history.replays_history", "历史命令 #{} 本身引用了历史命令, 不能重新执行"),
+    ("history.replay_too_deep", "历史命令嵌套过深 (最多{}层)"),
+    ("history.bad_offset", "无效的偏移量: {}"),
+    ("history.offset_range", "偏移量超出范围 (共{}条)"),
+    ("history.bad_id", "无效的历史命令ID: {}"),
+    ("history.no_id", "历史命令 #{} 不存在"),
+    ("history.save_failed", "无法保存历史记录: {}"),
+    ("exit.bye", "再见! (退出码: {})"),
+    ("script.done", "已执行脚本 {} ({}条命令)"),
+    ("script.too_deep", "脚本嵌套过深 (最多{}层)"),
+    ("script.interrupted", "{}: 脚本已中断"),
+    ("repl.welcome", "TypeCmd {} - 输入 'show help' 查看帮助"),
+    ("repl.exit_hint", "使用 exit 退出 TypeCmd"),
+    ("cli.needs_command", "{} 需要命令参数"),
+    ("cli.needs_path", "{} 需要文件路径"),
+    ("cli.needs_lang", "--lang 需要语言"),
+    ("cli.unknown_option", "未知的选项: {}"),
+    ("cli.extra_argument", "多余的参数: {}"),
+    ("cli.usage", "TypeCmd {}\n\n用法:\n  typecmd [选项]                 启动交互式命令行\n  typecmd [选项] <脚本文件>      执行脚本文件\n  typecmd [选项] -c <命令>       执行命令后退出\n\n选项:\n  -c, --command <命令>           执行命令 (可用 ; && || 连接多条)\n  -k, --keep-going               脚本出错时继续执行\n      --no-history               不读取也不保存历史记录\n      --history-file <路径>      指定历史记录文件\n      --config <路径>            指定配置文件 (默认 ~/.typecmdrc)\n      --lang <语言>              消息语言: zh-CN 或 en-US (默认取自 LANG)\n      --no-color                 关闭彩色输出\n  -q, --quiet                    只输出错误和警告\n  -h, --help                     显示帮助\n  -V, --version                  显示版本\n\n标准输入不是终端时, 逐行读取并执行其中的命令。"),
+];
+
+const EN_US: &[(&str, &str)] = &[
+    ("error.io", "I/O error: {}"),
+    ("error.parse", "Parse error: {}"),
+    ("error.command_not_found", "Command not found: {}"),
+    ("error.command_exists", "Command already exists: {}"),
+    ("error.insufficient_args", "Missing arguments: {}"),
+    ("error.undefined_variable", "Undefined variable: {}"),
+    ("error.invalid_history_command", "Invalid history command: {}"),
+    ("error.division_by_zero", "Division by zero"),
+    ("error.overflow", "Numeric overflow: {}"),
+    ("error.type_mismatch", "Type mismatch: {}"),
+    ("error.readonly", "Read-only variable cannot be changed or deleted: {}"),
+    ("error.other", "Other error: {}"),
+    ("parse.unclosed_quote", "unclosed quote"),
+    ("parse.chain_missing_tail", "missing command at the end of the chain"),
+    ("parse.chain_missing_command", "missing command before {}"),
+    ("parse.unclosed_variable", "unclosed variable reference ${"),
+    ("parse.empty_variable", "empty variable reference ${}"),
+    ("parse.empty_command", "empty command"),
+    ("parse.history_limit", "invalid history limit"),
+    ("parse.unknown_show", "unknown show subcommand: {}"),
+    ("parse.exit_code", "invalid exit code"),
+    ("parse.bool", "invalid boolean, expected true or false"),
+    ("parse.clear_target", "clear expects: vars or history"),
+    ("parse.vars_target", "save/load expect: vars [file]"),
+    ("parse.export_target", "export expects: json [file] or variable names"),
+    ("parse.export_json_name", "cannot export a variable named {} because export json writes JSON; use attr {} +exported"),
+    ("parse.import_target", "import expects: json <file> [--merge|--replace] or env <names...|--all>"),
+    ("parse.unknown_option", "unknown option: {}"),
+    ("parse.unclosed_substitution", "unclosed command substitution $("),
+    ("parse.pipe_missing_command", "missing command around pipe |"),
+    ("parse.line_count", "line count must be a non-negative integer: {}"),
+    ("parse.attr_flag", "attributes are +name or -name (readonly, exported, integer, hidden): {}"),
+    ("parse.history_arg", "invalid history argument"),
+    ("args.at_least_two", "{} needs at least 2 arguments"),
+    ("args.copy_extra", "too many arguments for copy, ignoring the rest"),
+    ("args.source_file", "source needs a file name"),
+    ("args.history_exec", "history command needs an argument"),
+    ("args.alias_expansion", "alias needs an expansion: alias name=command"),
+    ("args.unalias_name", "unalias needs an alias name"),
+    ("args.config_value", "config needs a key and a value"),
+    ("args.variable_name", "{} needs a variable name"),
+    ("args.calc_expr", "calc needs an expression"),
+    ("args.search_keyword", "search needs a keyword"),
+    ("args.import_file", "import json needs a file name"),
+    ("args.import_env", "import env needs variable names or --all"),
+    ("args.run_program", "run needs a program name"),
+    ("args.grep_text", "grep needs a text to search for"),
+    ("expr.empty", "empty expression"),
+    ("expr.trailing", "unexpected trailing input in expression: {}"),
+    ("expr.number", "invalid number: {}"),
+    ("expr.literal_too_large", "integer literal too large: {}"),
+    ("expr.unclosed_quote", "unclosed quote in expression"),
+    ("expr.invalid_char", "invalid character in expression: {}"),
+    ("expr.compare", "cannot compare {} with {}"),
+    ("expr.negate", "cannot negate {}"),
+    ("expr.missing_paren", "missing closing parenthesis in expression"),
+    ("expr.unexpected", "unexpected token in expression: {}"),
+    ("expr.unexpected_end", "unexpected end of expression"),
+    ("expr.operator", "cannot apply operator to {} and {}: {}"),
+    ("expr.expected", "expression is {}, expected {}"),
+    ("alias.invalid", "invalid alias name: {}"),
+    ("alias.none", "No aliases defined"),
+    ("alias.header", "Defined aliases ({}):"),
+    ("alias.not_found", "No such alias: {}"),
+    ("alias.set", "Alias \"{}\" set to '{}'"),
+    ("alias.removed", "Removed alias: {}"),
+    ("prefix.error", "Error: {}"),
+    ("prefix.warn", "Warning: {}"),
+    ("config.history_size", "invalid history size: {}"),
+    ("config.unknown_theme", "unknown theme: {}"),
+    ("config.unknown_key", "unknown setting: {}"),
+    ("config.bool", "invalid boolean: {}"),
+    ("config.unknown_lang", "unknown language: {}"),
+    ("config.no_file", "no configuration file"),
+    ("config.header", "Current settings ({}):"),
+    ("config.next_start", "{} takes effect at the next start"),
+    ("config.set", "Setting {} set to {}"),
+    ("config.autosave_failed", "failed to autosave variables: {}"),
+    ("theme.invalid_color", "invalid color: {}"),
+    ("theme.switched", "Switched to theme: {}"),
+    ("theme.available", "Available themes:"),
+    ("theme.styles", "Styles of the current theme:"),
+    ("plugin.summary", "Custom command"),
+    ("help.usage", "Usage: {} {}"),
+    ("help.aliases", "Aliases: {}"),
+    ("help.chain.always", "run a, then b"),
+    ("help.chain.and", "run b if a succeeded"),
+    ("help.chain.or", "run b if a failed"),
+    ("help.history.last", "run the previous command"),
+    ("help.history.id", "run history entry n"),
+    ("help.history.offset", "run the n-th most recent command"),
+    ("help.history.list", "show all history"),
+    ("help.history.recent", "show the last n commands"),
+    ("help.history.search", "search history for str"),
+    ("help.history.clear", "clear all history"),
+    ("help.expr.arith", "arithmetic, e.g. iset total (a + b * 2)"),
+    ("help.expr.logic", "comparison and logic"),
+    ("help.variables.expand", "expand a variable's value in arguments (not inside single quotes, \\$ for a literal $)"),
+    ("help.variables.env", "expand an environment variable"),
+    ("help.variables.capture", "run a command and insert its output"),
+    ("help.pipe.pass", "pass the output of a as input to b"),
+    ("help.pipe.set", "store the result of a pipeline in a variable"),
+    ("help.section.builtin", "Commands"),
+    ("help.section.plugins", "Extension commands"),
+    ("help.section.chain", "Command chains"),
+    ("help.section.history", "History"),
+    ("help.section.expr", "Expressions"),
+    ("help.section.variables", "Variable references"),
+    ("help.section.pipe", "Pipelines"),
+    ("help.title", "TypeCmd command line simulator"),
+    ("help.header", "Version: {}\nHistory: {} commands"),
+    ("main.see_help", "Run typecmd --help for usage"),
+    ("main.config_load", "Cannot load configuration file: {}"),
+    ("cmd.show.summary", "Show information"),
+    ("cmd.show.usage", "[help|ver|vars [--scope]|history|license|aliases|config]"),
+    ("cmd.help.summary", "Show help or command usage"),
+    ("cmd.help.usage", "[command]"),
+    ("cmd.exit.summary", "Exit the program"),
+    ("cmd.exit.usage", "[exit code]"),
+    ("cmd.set.summary", "Set a variable"),
+    ("cmd.set.usage", "<name> <value>"),
+    ("cmd.iset.summary", "Set an integer variable"),
+    ("cmd.iset.usage", "<name> <value|expression>"),
+    ("cmd.fset.summary", "Set a float variable"),
+    ("cmd.fset.usage", "<name> <value|expression>"),
+    ("cmd.bset.summary", "Set a boolean variable"),
+    ("cmd.bset.usage", "<name> <true|false>"),
+    ("cmd.local.summary", "Define a variable in the current scope"),
+    ("cmd.local.usage", "<name> <value>"),
+    ("cmd.const.summary", "Define a read-only constant"),
+    ("cmd.const.usage", "<name> <value>"),
+    ("cmd.readonly.summary", "Make a variable read-only"),
+    ("cmd.readonly.usage", "<name> [value]"),
+    ("cmd.attr.summary", "Show or change variable attributes"),
+    ("cmd.attr.usage", "<name> [+attribute|-attribute ...]"),
+    ("cmd.get.summary", "Get a variable"),
+    ("cmd.get.usage", "<name>"),
+    ("cmd.copy.summary", "Copy a variable"),
+    ("cmd.copy.usage", "<new name> <old name>"),
+    ("cmd.string.summary", "Print a string"),
+    ("cmd.string.usage", "<text>"),
+    ("cmd.int.summary", "Integer arithmetic"),
+    ("cmd.int.usage", "<expression>"),
+    ("cmd.calc.summary", "Evaluate an expression"),
+    ("cmd.calc.usage", "<expression>"),
+    ("cmd.list.summary", "List all variables"),
+    ("cmd.rm.summary", "Delete a variable"),
+    ("cmd.rm.usage", "<name>"),
+    ("cmd.clear.summary", "Clear all variables or history"),
+    ("cmd.clear.usage", "[vars|history]"),
+    ("cmd.history.summary", "Show command history"),
+    ("cmd.history.usage", "[n|search <keyword>|clear]"),
+    ("cmd.version.summary", "Same as show ver"),
+    ("cmd.source.summary", "Run a script file"),
+    ("cmd.source.usage", "<file>"),
+    ("cmd.alias.summary", "Define or list aliases"),
+    ("cmd.alias.usage", "[name=command]"),
+    ("cmd.unalias.summary", "Remove an alias"),
+    ("cmd.unalias.usage", "<name>"),
+    ("cmd.config.summary", "Change or show settings"),
+    ("cmd.config.usage", "[key value]"),
+    ("cmd.theme.summary", "Switch or list color themes"),
+    ("cmd.theme.usage", "[dark|light|mono]"),
+    ("cmd.save.summary", "Save variables to a file"),
+    ("cmd.save.usage", "vars [file]"),
+    ("cmd.load.summary", "Load variables from a file"),
+    ("cmd.load.usage", "vars [file]"),
+    ("cmd.export.summary", "Export variables as JSON, or mark them for spawned processes"),
+    ("cmd.export.usage", "json [file] | <names...>"),
+    ("cmd.import.summary", "Import variables from a JSON file or the environment"),
+    ("cmd.import.usage", "json <file> [--merge|--replace] | env <names...|--all>"),
+    ("cmd.env.summary", "List environment variables or show one"),
+    ("cmd.env.usage", "[name]"),
+    ("cmd.run.summary", "Run an external program"),
+    ("cmd.run.usage", "<program> [args...]"),
+    ("cmd.upper.summary", "Convert input to upper case"),
+    ("cmd.lower.summary", "Convert input to lower case"),
+    ("cmd.trim.summary", "Remove leading and trailing whitespace from input"),
+    ("cmd.lines.summary", "Number each line of input"),
+    ("cmd.grep.summary", "Keep lines containing a text"),
+    ("cmd.grep.usage", "<text>"),
+    ("cmd.count.summary", "Count lines of input"),
+    ("cmd.head.summary", "Keep the first n lines of input (default 10)"),
+    ("cmd.head.usage", "[n]"),
+    ("cmd.tail.summary", "Keep the last n lines of input (default 10)"),
+    ("cmd.tail.usage", "[n]"),
+    ("cmd.last.summary", "Run the previous command"),
+    ("cmd.history_exec.summary", "Run history entry n, or the n-th most recent"),
+    ("cmd.history_exec.usage", "<n|-n>"),
+    ("vars.none", "No variables defined"),
+    ("vars.header", "Defined variables ({}):"),
+    ("vars.set", "Variable \"{}\" set to \"{}\" ({})"),
+    ("vars.local_set", "Local variable \"{}\" set to \"{}\" ({})"),
+    ("vars.const_set", "Constant \"{}\" set to \"{}\" ({})"),
+    ("vars.readonly_set", "Variable \"{}\" is now read-only"),
+    ("vars.attributes", "Attributes of {}: {}"),
+    ("vars.no_attributes", "none"),
+    ("vars.integer_only", "integer variable {} cannot hold a {} value"),
+    ("vars.readonly_kept", "kept {} read-only variables"),
+    ("vars.exported_env", "Exported variables: {}"),
+    ("vars.value", "Variable {} is: {} ({})"),
+    ("vars.copied", "Variable \"{}\" set to the value of \"{}\": \"{}\" ({})"),
+    ("vars.deleted", "Deleted variable: {}"),
+    ("vars.not_found", "No such variable: {}"),
+    ("vars.cleared", "Cleared all variables ({})"),
+    ("vars.saved", "Saved {} variables to {}"),
+    ("vars.loaded", "Loaded variables from {} ({} in total)"),
+    ("vars.skipped", "{}: skipped {} invalid lines"),
+    ("vars.exported", "Exported {} variables to {}"),
+    ("vars.imported", "Imported variables from {} ({} in total)"),
+    ("vars.replaced", "Replaced all variables with the ones from {} ({} in total)"),
+    ("vars.invalid_json", "invalid JSON: {}"),
+    ("vars.json_no_attributes", "JSON does not store attributes; the attributes of {} variables were left out"),
+    ("vars.scope_header", "{} scope ({}):"),
+    ("vars.scope.global", "Global"),
+    ("vars.scope.session", "Session"),
+    ("vars.scope.script", "Script"),
+    ("vars.scope.block", "Block"),
+    ("env.header", "Environment variables ({}):"),
+    ("env.value", "Environment variable {} is: {}"),
+    ("env.not_set", "environment variable not set: {}"),
+    ("env.imported", "Imported {} variables from the environment"),
+    ("run.failed", "cannot run {}: {}"),
+    ("run.exit_status", "{} exited with status {}"),
+    ("pipe.no_input", "{} needs piped input, e.g. history | {}"),
+    ("history.empty", "History is empty"),
+    ("history.recent", "last {}"),
+    ("history.all", "all"),
+    ("history.header", "Command history ({}):"),
+    ("history.hint", "Use !<id> to run a history entry"),
+    ("history.cleared_all", "Cleared all history"),
+    ("history.cleared", "History cleared"),
+    ("history.no_match", "No history entries contain \"{}\""),
+    ("history.search_header", "Results for \"{}\" ({}):"),
+    ("history.running", "Running history entry #{}: {}"),
+    ("history.nothing", "No history to run"),
+    ("history.replays_history", "history entry #{} refers to history itself and cannot be run again"),
+    ("history.replay_too_deep", "history replays nested too deeply (at most {} levels)"),
+    ("history.bad_offset", "invalid offset: {}"),
+    ("history.offset_range", "offset out of range ({} entries)"),
+    ("history.bad_id", "invalid history id: {}"),
+    ("history.no_id", "History entry #{} does not exist"),
+    ("history.save_failed", "Cannot save history: {}"),
+    ("exit.bye", "Bye! (exit code: {})"),
+    ("script.done", "Ran script {} ({} commands)"),
+    ("script.too_deep", "scripts nested too deeply (at most {} levels)"),
+    ("script.interrupted", "{}: script interrupted"),
+    ("repl.welcome", "TypeCmd {} - type 'show help' for help"),
+    ("repl.exit_hint", "Use exit to leave TypeCmd"),
+    ("cli.needs_command", "{} needs a command"),
+    ("cli.needs_path", "{} needs a file path"),
+    ("cli.needs_lang", "--lang needs a language"),
+    ("cli.unknown_option", "unknown option: {}"),
+    ("cli.extra_argument", "unexpected argument: {}"),
+    ("cli.usage", "TypeCmd {}\n\nUsage:\n  typecmd [options]                start the interactive prompt\n  typecmd [options] <script>       run a script file\n  typecmd [options] -c <command>   run commands and exit\n\nOptions:\n  -c, --command <command>          run commands (chain with ; && ||)\n  -k, --keep-going                 keep running a script after errors\n      --no-history                 do not load or save history\n      --history-file <path>        history file to use\n      --config <path>              configuration file (default ~/.typecmdrc)\n      --lang <language>            message language: zh-CN or en-US (default from LANG)\n      --no-color                   disable colored output\n  -q, --quiet                      only print errors and warnings\n  -h, --help                       show this help\n  -V, --version                    show the version\n\nWhen stdin is not a terminal, commands are read from it line by line."),
+];
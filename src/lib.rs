@@ -3,30 +3,64 @@
 //! This crate provides a command line interpreter with history support,
 //! variable storage, and colorized output.
 
+pub mod i18n;
 pub mod error;
 pub mod colors;
+pub mod theme;
 pub mod history;
+pub mod config;
+pub mod value;
 pub mod variables;
-pub mod variablesint;
+pub mod aliases;
+pub mod expr;
+pub mod filter;
+pub mod output;
+pub mod editor;
+pub mod prompt;
+pub mod completion;
+pub mod signal;
 pub mod parser;
 pub mod command;
+pub mod registry;
+pub mod plugin;
 pub mod executor;
 pub mod repl;
+#[cfg(feature = "cli")]
+pub mod cli;
 
 // Re-export commonly used items
 pub use error::{TypeCmdError, Result};
-pub use history::HistoryManager;
-pub use variables::VariableStore;
-pub use command::{Command, ShowSubcommand, ClearTarget, HistorySubcommand};
+pub use i18n::{Lang, set_lang};
+pub use history::{HistoryConfig, HistoryManager};
+pub use config::Config;
+pub use theme::{Role, Style, Theme};
+pub use value::Value;
+pub use variables::{Attributes, Scope, ScopeKind, VariableStore};
+pub use aliases::AliasStore;
+pub use command::{Command, ShowSubcommand, ClearTarget, HistorySubcommand, AliasSubcommand, NumericType, Connector};
+pub use filter::Filter;
 pub use executor::TypeCmd;
+pub use registry::{CommandSpec, ArgKind, Handler, BUILTIN_COMMANDS, find_command};
+pub use plugin::{CommandHandler, CommandContext};
+pub use output::{Output, OutputKind, OutputLine, StdoutOutput, BufferedOutput, NullOutput, QuietOutput};
 
 /// Prelude module for convenient imports
 pub mod prelude {
     pub use crate::error::{TypeCmdError, Result};
-    pub use crate::colors::{colorize, print_error, print_success, print_info, print_warn, print_gray, print_cyan};
-    pub use crate::history::HistoryManager;
-    pub use crate::variables::VariableStore;
-    pub use crate::command::{Command, ShowSubcommand, ClearTarget, HistorySubcommand};
+    pub use crate::i18n::{Lang, set_lang};
+    pub use crate::tr;
+    pub use crate::colors::{colorize, paint, set_color_enabled, set_theme, terminal_supports_color, set_quiet, print_text, print_line, print_error, print_success, print_info, print_warn, print_gray, print_cyan};
+    pub use crate::history::{HistoryConfig, HistoryManager};
+    pub use crate::config::Config;
+    pub use crate::theme::{Role, Style, Theme};
+    pub use crate::value::Value;
+    pub use crate::variables::{Attributes, ScopeKind, VariableStore};
+    pub use crate::aliases::AliasStore;
+    pub use crate::command::{Command, ShowSubcommand, ClearTarget, HistorySubcommand, AliasSubcommand, NumericType, Connector};
+    pub use crate::filter::Filter;
     pub use crate::executor::TypeCmd;
-    pub use crate::parser::{parse_command, parse_to_command};
+    pub use crate::plugin::{CommandHandler, CommandContext};
+    pub use crate::output::{Output, OutputKind, OutputLine, StdoutOutput, BufferedOutput, NullOutput, QuietOutput};
+    pub use crate::expr::evaluate;
+    pub use crate::parser::{parse_command, parse_to_command, split_chain, split_pipeline};
 }
\ No newline at end of file
@@ -1,6 +1,108 @@
 use typecmd::prelude::*;
 
+#[cfg(feature = "cli")]
+fn main() {
+    use std::env;
+    use std::io::{self, IsTerminal};
+    use std::process::exit;
+    use typecmd::cli::{usage, CliOptions};
+    
+    set_lang(Lang::from_env());
+    set_color_enabled(terminal_supports_color());
+    let options = match CliOptions::from_env() {
+        Ok(options) => options,
+        Err(e) => {
+            print_error(&format!("{}", e));
+            eprintln!("{}", tr!("main.see_help"));
+            exit(2);
+        }
+    };
+    
+    if let Some(lang) = options.lang {
+        set_lang(lang);
+    }
+    if options.show_help {
+        print_line(&usage());
+        return;
+    }
+    if options.show_version {
+        print_line(&format!("TypeCmd {}", env!("CARGO_PKG_VERSION")));
+        return;
+    }
+    
+    let loaded = match &options.config {
+        Some(path) => Config::load(path),
+        None => Config::load_default(),
+    };
+    let mut config = match loaded {
+        Ok(config) => config,
+        Err(e) => {
+            print_error(&tr!("main.config_load", e));
+            exit(2);
+        }
+    };
+    
+    if let Some(lang) = options.lang.or(config.lang) {
+        set_lang(lang);
+    }
+    if options.no_history {
+        config.history.persist = false;
+    }
+    if let Some(path) = &options.history_file {
+        let path = env::current_dir().map(|dir| dir.join(path)).unwrap_or_else(|_| path.clone());
+        config.history.history_file = path.display().to_string();
+    }
+    if options.no_color {
+        config.color = false;
+    }
+    
+    set_color_enabled(config.color && terminal_supports_color());
+    set_theme(config.theme.clone());
+    set_quiet(options.quiet);
+    
+    let result = TypeCmd::with_config(config).and_then(|mut typecmd| {
+        if let Some(command) = &options.command {
+            Ok(typecmd.run_command(command).unwrap_or_else(|e| {
+                print_error(&format!("{}", e));
+                typecmd.exit_requested().unwrap_or(typecmd.last_status())
+            }))
+        } else if let Some(script) = &options.script {
+            match typecmd.run_script(script, options.keep_going) {
+                Ok(failed) => Ok(match typecmd.exit_requested() {
+                    Some(code) => code,
+                    None if options.keep_going && failed > 0 => 1,
+                    None => 0,
+                }),
+                Err(e) => {
+                    print_error(&format!("{}", e));
+                    Ok(1)
+                }
+            }
+        } else if !io::stdin().is_terminal() {
+            typecmd.run_reader(io::stdin().lock())
+        } else {
+            typecmd.run()
+        }
+    });
+    
+    match result {
+        Ok(code) => exit(code),
+        Err(e) => {
+            print_error(&format!("{}", e));
+            exit(1);
+        }
+    }
+}
+
+#[cfg(not(feature = "cli"))]
 fn main() -> Result<()> {
-    let mut typecmd = TypeCmd::new()?;
-    typecmd.run()
-}
\ No newline at end of file
+    set_lang(Lang::from_env());
+    let config = Config::load_default()?;
+    if let Some(lang) = config.lang {
+        set_lang(lang);
+    }
+    set_color_enabled(config.color && terminal_supports_color());
+    set_theme(config.theme.clone());
+    let code = TypeCmd::with_config(config)?.run()?;
+    std::process::exit(code);
+}
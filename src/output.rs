@@ -0,0 +1,184 @@
+//! Output sinks for command messages
+//!
+//! `TypeCmd` writes every message through an `Output` so embedders can
+//! capture or discard it instead of printing to the terminal.
+
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+
+use crate::colors::{print_error, print_gray, print_info, print_success, print_warn};
+
+/// Kind of an output message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputKind {
+    Info,
+    Success,
+    Warn,
+    Error,
+    Muted,
+}
+
+/// A single captured output message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputLine {
+    pub kind: OutputKind,
+    pub text: String,
+}
+
+/// Destination for messages produced by commands
+pub trait Output {
+    /// Write a message of the given kind
+    fn write(&self, kind: OutputKind, msg: &str);
+    
+    /// Write an informational message
+    fn info(&self, msg: &str) {
+        self.write(OutputKind::Info, msg);
+    }
+    
+    /// Write a success message
+    fn success(&self, msg: &str) {
+        self.write(OutputKind::Success, msg);
+    }
+    
+    /// Write a warning
+    fn warn(&self, msg: &str) {
+        self.write(OutputKind::Warn, msg);
+    }
+    
+    /// Write an error
+    fn error(&self, msg: &str) {
+        self.write(OutputKind::Error, msg);
+    }
+    
+    /// Write a low-priority message
+    fn muted(&self, msg: &str) {
+        self.write(OutputKind::Muted, msg);
+    }
+}
+
+/// Colorized output to stdout
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StdoutOutput;
+
+impl Output for StdoutOutput {
+    fn write(&self, kind: OutputKind, msg: &str) {
+        match kind {
+            OutputKind::Info => print_info(msg),
+            OutputKind::Success => print_success(msg),
+            OutputKind::Warn => print_warn(msg),
+            OutputKind::Error => print_error(msg),
+            OutputKind::Muted => print_gray(msg),
+        }
+    }
+}
+
+/// Output that records messages in memory.
+///
+/// Clones share the same buffer, so a clone can be handed to `TypeCmd`
+/// while the original is kept to read the captured lines.
+#[derive(Debug, Clone, Default)]
+pub struct BufferedOutput {
+    lines: Arc<Mutex<Vec<OutputLine>>>,
+}
+
+impl BufferedOutput {
+    /// Create a new empty buffer
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Get a copy of the captured lines
+    pub fn lines(&self) -> Vec<OutputLine> {
+        self.lines.lock().unwrap().clone()
+    }
+    
+    /// Remove and return the captured lines
+    pub fn take(&self) -> Vec<OutputLine> {
+        std::mem::take(&mut *self.lines.lock().unwrap())
+    }
+    
+    /// Get the captured text, one message per line
+    pub fn text(&self) -> String {
+        self.lines
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|line| line.text.as_str())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl Output for BufferedOutput {
+    fn write(&self, kind: OutputKind, msg: &str) {
+        self.lines.lock().unwrap().push(OutputLine {
+            kind,
+            text: msg.to_string(),
+        });
+    }
+}
+
+/// Output that discards every message
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NullOutput;
+
+impl Output for NullOutput {
+    fn write(&self, _kind: OutputKind, _msg: &str) {}
+}
+
+/// Shared outputs forward to the output they point to
+impl<T: Output + ?Sized> Output for Rc<T> {
+    fn write(&self, kind: OutputKind, msg: &str) {
+        (**self).write(kind, msg);
+    }
+}
+
+/// Output that forwards only warnings and errors
+pub struct QuietOutput(pub Box<dyn Output>);
+
+impl Output for QuietOutput {
+    fn write(&self, kind: OutputKind, msg: &str) {
+        if matches!(kind, OutputKind::Warn | OutputKind::Error) {
+            self.0.write(kind, msg);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(kind: OutputKind, text: &str) -> OutputLine {
+        OutputLine { kind, text: text.to_string() }
+    }
+
+    #[test]
+    fn buffered_output_shares_its_buffer() {
+        let output = BufferedOutput::new();
+        let sink = output.clone();
+        sink.info("one");
+        sink.warn("two");
+        sink.muted("three");
+        assert_eq!(output.lines(), vec![
+            line(OutputKind::Info, "one"),
+            line(OutputKind::Warn, "two"),
+            line(OutputKind::Muted, "three"),
+        ]);
+        assert_eq!(output.text(), "one\ntwo\nthree");
+
+        assert_eq!(output.take().len(), 3);
+        assert!(sink.lines().is_empty());
+    }
+
+    #[test]
+    fn quiet_output_keeps_problems_only() {
+        let output = BufferedOutput::new();
+        let quiet = QuietOutput(Box::new(output.clone()));
+        quiet.info("info");
+        quiet.success("success");
+        quiet.muted("muted");
+        quiet.warn("warn");
+        quiet.error("error");
+        assert_eq!(output.lines(), vec![line(OutputKind::Warn, "warn"), line(OutputKind::Error, "error")]);
+    }
+}
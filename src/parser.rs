@@ -1,9 +1,268 @@
-use crate::colors::print_warn;
+use std::iter::Peekable;
+use std::str::Chars;
+
 use crate::error::{TypeCmdError, Result};
-use crate::command::{Command, ShowSubcommand, ClearTarget, HistorySubcommand};
+use crate::filter::{Filter, DEFAULT_LINES};
+use crate::command::{Command, ShowSubcommand, ClearTarget, HistorySubcommand, AliasSubcommand, NumericType, Connector};
+use crate::registry::find_command;
+use crate::tr;
+use crate::value::Value;
+use crate::variables::Attributes;
+
+/// A piece of a command line, as seen by `Scanner`
+enum Piece<'a> {
+    /// A character outside quotes, parentheses and substitutions, where
+    /// separators take effect
+    Bare(char),
+    /// Text kept as written: escapes, quotes and anything inside them or
+    /// inside parentheses
+    Text(&'a str),
+    /// A `$(...)` substitution: its full text and the command inside
+    Substitution(&'a str, &'a str),
+}
+
+/// Walks a command line tracking quotes, parentheses and `$(...)`, so that
+/// splitting chains and pipelines and expanding substitutions agree on
+/// where a command ends.
+///
+/// Backslash escapes the next character except inside single quotes.
+/// `$(` starts a substitution outside single quotes; its command is scanned
+/// with fresh quote state up to the matching `)`.
+struct Scanner<'a> {
+    input: &'a str,
+    pos: usize,
+    in_quotes: bool,
+    in_single_quotes: bool,
+    depth: usize,
+}
+
+impl<'a> Scanner<'a> {
+    fn new(input: &'a str) -> Self {
+        Scanner {
+            input,
+            pos: 0,
+            in_quotes: false,
+            in_single_quotes: false,
+            depth: 0,
+        }
+    }
+    
+    /// Whether the scan is inside quotes
+    fn quoted(&self) -> bool {
+        self.in_quotes || self.in_single_quotes
+    }
+    
+    fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+    
+    /// Read the rest of a `$(...)` starting at `start`, after its `$(`
+    fn substitution(&mut self, start: usize) -> Result<Piece<'a>> {
+        let rest = &self.input[self.pos..];
+        let mut inner = Scanner::new(rest);
+        while let Some(piece) = inner.next() {
+            if let Piece::Bare(')') = piece? {
+                let command = &rest[..inner.pos - 1];
+                self.pos += inner.pos;
+                return Ok(Piece::Substitution(&self.input[start..self.pos], command));
+            }
+        }
+        Err(TypeCmdError::Parse(tr!("parse.unclosed_substitution")))
+    }
+}
+
+impl<'a> Iterator for Scanner<'a> {
+    type Item = Result<Piece<'a>>;
+    
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = self.pos;
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        let quoted = self.quoted();
+        match c {
+            '\\' if !self.in_single_quotes => {
+                if let Some(next_c) = self.peek() {
+                    self.pos += next_c.len_utf8();
+                }
+            }
+            '"' if !self.in_single_quotes => self.in_quotes = !self.in_quotes,
+            '\'' if !self.in_quotes => self.in_single_quotes = !self.in_single_quotes,
+            '$' if !self.in_single_quotes && self.peek() == Some('(') => {
+                self.pos += 1;
+                return Some(self.substitution(start));
+            }
+            '(' if !quoted => self.depth += 1,
+            ')' if !quoted && self.depth > 0 => self.depth -= 1,
+            _ if !quoted && self.depth == 0 => return Some(Ok(Piece::Bare(c))),
+            _ => {}
+        }
+        Some(Ok(Piece::Text(&self.input[start..self.pos])))
+    }
+}
+
+/// Split a line into commands joined by `;`, `&&` and `||`.
+///
+/// Separators inside quotes, parentheses or `$(...)` are kept as part of
+/// the command, so expressions such as `calc (a > 1 && b < 2)` are not
+/// split. The first command is always paired with `Connector::Always`.
+pub fn split_chain(input: &str) -> Result<Vec<(Connector, String)>> {
+    let mut chain = Vec::new();
+    let mut current = String::new();
+    let mut connector = Connector::Always;
+    let mut scanner = Scanner::new(input);
+    
+    while let Some(piece) = scanner.next() {
+        match piece? {
+            Piece::Bare(';') => {
+                push_chain_segment(&mut chain, connector, &mut current, ";")?;
+                connector = Connector::Always;
+            }
+            Piece::Bare(c @ ('&' | '|')) if scanner.peek() == Some(c) => {
+                scanner.next();
+                let op = if c == '&' { "&&" } else { "||" };
+                push_chain_segment(&mut chain, connector, &mut current, op)?;
+                connector = if c == '&' { Connector::And } else { Connector::Or };
+            }
+            Piece::Bare(c) => current.push(c),
+            Piece::Text(text) | Piece::Substitution(text, _) => current.push_str(text),
+        }
+    }
+    
+    if scanner.quoted() {
+        return Err(TypeCmdError::Parse(tr!("parse.unclosed_quote")));
+    }
+    
+    let last = current.trim();
+    if !last.is_empty() {
+        chain.push((connector, last.to_string()));
+    } else if connector != Connector::Always {
+        return Err(TypeCmdError::Parse(tr!("parse.chain_missing_tail")));
+    }
+    
+    Ok(chain)
+}
+
+/// Push a finished chain segment, rejecting empty commands around `&&`/`||`
+fn push_chain_segment(
+    chain: &mut Vec<(Connector, String)>,
+    connector: Connector,
+    current: &mut String,
+    separator: &str,
+) -> Result<()> {
+    let segment = current.trim().to_string();
+    current.clear();
+    
+    if segment.is_empty() {
+        if connector != Connector::Always || separator != ";" {
+            return Err(TypeCmdError::Parse(tr!("parse.chain_missing_command", separator)));
+        }
+        return Ok(());
+    }
+    
+    chain.push((connector, segment));
+    Ok(())
+}
+
+/// Split a command into the stages of a `|` pipeline.
+///
+/// Like `split_chain`, separators inside quotes, parentheses or `$(...)`
+/// are kept as part of the command. Empty stages are rejected.
+pub fn split_pipeline(input: &str) -> Result<Vec<String>> {
+    let mut stages = Vec::new();
+    let mut current = String::new();
+    
+    for piece in Scanner::new(input) {
+        match piece? {
+            Piece::Bare('|') => push_pipeline_stage(&mut stages, &mut current)?,
+            Piece::Bare(c) => current.push(c),
+            Piece::Text(text) | Piece::Substitution(text, _) => current.push_str(text),
+        }
+    }
+    
+    if stages.is_empty() {
+        stages.push(current.trim().to_string());
+    } else {
+        push_pipeline_stage(&mut stages, &mut current)?;
+    }
+    Ok(stages)
+}
+
+/// Check whether any command of `line` is a history reference such as
+/// `!!` or `! n`
+pub(crate) fn references_history(line: &str) -> Result<bool> {
+    for (_, segment) in split_chain(line)? {
+        for stage in split_pipeline(&segment)? {
+            if stage.starts_with('!') {
+                return Ok(true);
+            }
+        }
+    }
+    Ok(false)
+}
+
+fn push_pipeline_stage(stages: &mut Vec<String>, current: &mut String) -> Result<()> {
+    let stage = current.trim();
+    if stage.is_empty() {
+        return Err(TypeCmdError::Parse(tr!("parse.pipe_missing_command")));
+    }
+    stages.push(stage.to_string());
+    current.clear();
+    Ok(())
+}
+
+/// Replace every `$(command)` outside single quotes with the output of
+/// `run(command)`.
+///
+/// Trailing newlines of the output are dropped and quotes, `$` and
+/// backslashes in it are escaped, so tokenizing the result keeps the output
+/// as literal text; unquoted spaces in the output still separate arguments.
+pub fn expand_substitutions<F>(input: &str, mut run: F) -> Result<String>
+where
+    F: FnMut(&str) -> Result<String>,
+{
+    let mut out = String::with_capacity(input.len());
+    
+    for piece in Scanner::new(input) {
+        match piece? {
+            Piece::Substitution(_, command) => {
+                for c in run(command)?.trim_end_matches(['\n', '\r']).chars() {
+                    if matches!(c, '\\' | '"' | '\'' | '$') {
+                        out.push('\\');
+                    }
+                    out.push(c);
+                }
+            }
+            Piece::Bare(c) => out.push(c),
+            Piece::Text(text) => out.push_str(text),
+        }
+    }
+    
+    Ok(out)
+}
 
 /// Parse a command string into tokens
 pub fn parse_command(input: &str) -> Result<Vec<String>> {
+    tokenize(input, None)
+}
+
+/// Parse a command string into tokens, expanding `$name` and `${name}`
+/// references through `lookup`. Environment references `$ENV{NAME}` are
+/// passed to `lookup` as `ENV{NAME}`, see `env_reference`.
+///
+/// Expansion happens outside quotes and inside double quotes, but not inside
+/// single quotes. A literal `$` can be written as `\$`; backslashes inside
+/// single quotes are kept as written.
+pub fn parse_command_with<F>(input: &str, mut lookup: F) -> Result<Vec<String>>
+where
+    F: FnMut(&str) -> Result<String>,
+{
+    tokenize(input, Some(&mut lookup))
+}
+
+/// Callback used to resolve variable references during tokenization
+type Lookup<'a> = &'a mut dyn FnMut(&str) -> Result<String>;
+
+fn tokenize(input: &str, mut lookup: Option<Lookup>) -> Result<Vec<String>> {
     let mut tokens = Vec::new();
     let mut current_token = String::new();
     let mut in_quotes = false;
@@ -12,7 +271,7 @@ pub fn parse_command(input: &str) -> Result<Vec<String>> {
     
     while let Some(c) = chars.next() {
         match c {
-            '\\' => {
+            '\\' if !in_single_quotes => {
                 if let Some(next_c) = chars.next() {
                     current_token.push(next_c);
                 }
@@ -23,6 +282,16 @@ pub fn parse_command(input: &str) -> Result<Vec<String>> {
             '\'' if !in_quotes => {
                 in_single_quotes = !in_single_quotes;
             }
+            '$' if !in_single_quotes && lookup.is_some() => {
+                match read_reference(&mut chars)? {
+                    Some(name) => {
+                        if let Some(lookup) = lookup.as_mut() {
+                            current_token.push_str(&lookup(&name)?);
+                        }
+                    }
+                    None => current_token.push(c),
+                }
+            }
             ' ' if !in_quotes && !in_single_quotes => {
                 if !current_token.is_empty() {
                     tokens.push(current_token.clone());
@@ -40,135 +309,442 @@ pub fn parse_command(input: &str) -> Result<Vec<String>> {
     }
     
     if in_quotes || in_single_quotes {
-        return Err(TypeCmdError::Parse("未闭合的引号".to_string()));
+        return Err(TypeCmdError::Parse(tr!("parse.unclosed_quote")));
     }
     
     Ok(tokens)
 }
 
+/// Read the variable name following a `$`, either `name` or `{name}`.
+/// Returns `None` when the `$` is not followed by a variable reference.
+pub(crate) fn read_variable_name(chars: &mut Peekable<Chars>) -> Result<Option<String>> {
+    let mut name = String::new();
+
+    if chars.peek() == Some(&'{') {
+        chars.next();
+        loop {
+            match chars.next() {
+                Some('}') => break,
+                Some(c) => name.push(c),
+                None => return Err(TypeCmdError::Parse(tr!("parse.unclosed_variable"))),
+            }
+        }
+        if name.is_empty() {
+            return Err(TypeCmdError::Parse(tr!("parse.empty_variable")));
+        }
+        return Ok(Some(name));
+    }
+
+    while let Some(&c) = chars.peek() {
+        if c.is_alphanumeric() || c == '_' {
+            name.push(c);
+            chars.next();
+        } else {
+            break;
+        }
+    }
+
+    Ok(if name.is_empty() { None } else { Some(name) })
+}
+
+/// Read the reference following a `$`: a variable name as returned by
+/// `read_variable_name`, or `ENV{NAME}` for `$ENV{NAME}`
+pub(crate) fn read_reference(chars: &mut Peekable<Chars>) -> Result<Option<String>> {
+    let name = read_variable_name(chars)?;
+    if name.as_deref() == Some("ENV") && chars.peek() == Some(&'{') {
+        let var = read_variable_name(chars)?.expect("braced names are never empty");
+        return Ok(Some(format!("ENV{{{}}}", var)));
+    }
+    Ok(name)
+}
+
+/// Name of the environment variable an `ENV{NAME}` reference points to
+pub fn env_reference(reference: &str) -> Option<&str> {
+    reference.strip_prefix("ENV{")?.strip_suffix('}')
+}
+
 /// Parse tokens into a Command enum
 pub fn parse_to_command(tokens: Vec<String>) -> Result<Command> {
     if tokens.is_empty() {
-        return Err(TypeCmdError::Parse("空命令".to_string()));
+        return Err(TypeCmdError::Parse(tr!("parse.empty_command")));
     }
     
     let cmd = tokens[0].to_lowercase();
     let args = &tokens[1..];
     
-    match cmd.as_str() {
-        "show" => parse_show_command(args),
-        "exit" | "quit" | "q" => parse_exit_command(args),
-        "to" | "var" | "let" | "set" => parse_set_command(args),
-        "ito" | "ivar" | "ilet" | "iset" => parse_iset_command(args),
-        "get" | "which" | "echo" => parse_get_command(args),
-        "string" | "str" | "sprint" => parse_string_command(args),
-        "int" | "num" => parse_int_command(args),
-        "ls" | "list" => Ok(Command::List),
-        "rm" | "del" | "unset" => parse_delete_command(args),
-        "clear" | "cls" => parse_clear_command(args),
-        "history" | "hist" => parse_history_command(args),
-        "copy" | "cpvar" => parse_copy_command(args),
-        "ver" | "version" => {
-            let vstr = "ver";
-            let ccc = vec![vstr.to_string()];
-            let args = &ccc[0..];
-            parse_show_command(args)
-        }, 
-        "!!" => Ok(Command::LastCommand),
-        "!" => {
-            if args.is_empty() {
-                Err(TypeCmdError::InsufficientArgs("历史命令需要参数".to_string()))
-            } else {
-                Ok(Command::HistoryCommand(args[0].clone()))
-            }
-        }
-        _ => Err(TypeCmdError::CommandNotFound(cmd)),
+    match find_command(&cmd) {
+        Some(spec) => (spec.parse)(args),
+        None => Err(TypeCmdError::CommandNotFound(cmd)),
     }
 }
 
-fn parse_copy_command(args: &[String]) -> Result<Command> {
+pub(crate) fn parse_copy_command(args: &[String]) -> Result<Command> {
     if args.len() < 2 {
         return Err(TypeCmdError::InsufficientArgs(
-            "copy命令需要至少2个参数".to_string(),
+            tr!("args.at_least_two", "copy"),
         ));
-    } else if args.len() > 2 {
-        print_warn("copy命令参数过多, 忽略剩余参数");
     }
-    Ok(Command::Copy(args[0].clone(), args[1].clone()))
+    Ok(Command::Copy(args[0].clone(), args[1].clone(), args.len() > 2))
 }
-fn parse_show_command(args: &[String]) -> Result<Command> {
+pub(crate) fn parse_source_command(args: &[String]) -> Result<Command> {
     if args.is_empty() {
-        return Ok(Command::Show(ShowSubcommand::Help));
+        return Err(TypeCmdError::InsufficientArgs(
+            tr!("args.source_file"),
+        ));
+    }
+    
+    Ok(Command::Source(args[0].clone()))
+}
+
+pub(crate) fn parse_help_command(args: &[String]) -> Result<Command> {
+    Ok(Command::Show(ShowSubcommand::Help(args.first().cloned())))
+}
+
+pub(crate) fn parse_version_command(_args: &[String]) -> Result<Command> {
+    Ok(Command::Show(ShowSubcommand::Version))
+}
+
+pub(crate) fn parse_list_command(_args: &[String]) -> Result<Command> {
+    Ok(Command::List)
+}
+
+pub(crate) fn parse_last_command(_args: &[String]) -> Result<Command> {
+    Ok(Command::LastCommand)
+}
+
+pub(crate) fn parse_history_exec_command(args: &[String]) -> Result<Command> {
+    if args.is_empty() {
+        Err(TypeCmdError::InsufficientArgs(tr!("args.history_exec")))
+    } else {
+        Ok(Command::HistoryCommand(args[0].clone()))
+    }
+}
+
+pub(crate) fn parse_alias_command(args: &[String]) -> Result<Command> {
+    if args.is_empty() {
+        return Ok(Command::Alias(AliasSubcommand::List));
+    }
+    
+    let definition = args.join(" ");
+    match definition.split_once('=') {
+        Some((name, expansion)) if !expansion.trim().is_empty() => Ok(Command::Alias(
+            AliasSubcommand::Define(name.to_string(), expansion.trim().to_string()),
+        )),
+        Some(_) => Err(TypeCmdError::InsufficientArgs(
+            tr!("args.alias_expansion"),
+        )),
+        None => Ok(Command::Alias(AliasSubcommand::Show(args[0].clone()))),
+    }
+}
+
+pub(crate) fn parse_unalias_command(args: &[String]) -> Result<Command> {
+    if args.is_empty() {
+        return Err(TypeCmdError::InsufficientArgs(
+            tr!("args.unalias_name"),
+        ));
+    }
+    
+    Ok(Command::Alias(AliasSubcommand::Remove(args[0].clone())))
+}
+
+pub(crate) fn parse_config_command(args: &[String]) -> Result<Command> {
+    match args.len() {
+        0 => Ok(Command::Show(ShowSubcommand::Config)),
+        1 => Err(TypeCmdError::InsufficientArgs(
+            tr!("args.config_value"),
+        )),
+        _ => Ok(Command::Config(args[0].clone(), args[1..].join(" "))),
+    }
+}
+
+pub(crate) fn parse_theme_command(args: &[String]) -> Result<Command> {
+    Ok(Command::Theme(args.first().cloned()))
+}
+
+pub(crate) fn parse_save_command(args: &[String]) -> Result<Command> {
+    parse_vars_target(args)?;
+    Ok(Command::SaveVars(args.get(1).cloned()))
+}
+
+pub(crate) fn parse_load_command(args: &[String]) -> Result<Command> {
+    parse_vars_target(args)?;
+    Ok(Command::LoadVars(args.get(1).cloned()))
+}
+
+/// `save` and `load` only work on variables for now
+fn parse_vars_target(args: &[String]) -> Result<()> {
+    match args.first() {
+        Some(target) if target.to_lowercase() == "vars" => Ok(()),
+        _ => Err(TypeCmdError::Parse(tr!("parse.vars_target"))),
+    }
+}
+
+pub(crate) fn parse_export_command(args: &[String]) -> Result<Command> {
+    match args.first() {
+        Some(format) if format.to_lowercase() == "json" => {
+            Ok(Command::ExportJson(args.get(1).cloned()))
+        }
+        // `export json` always means the JSON document, so a variable of
+        // that name can't be exported anywhere in the list either
+        Some(_) => match args.iter().find(|name| name.to_lowercase() == "json") {
+            Some(name) => Err(TypeCmdError::Parse(tr!("parse.export_json_name", name, name))),
+            None => Ok(Command::Export(args.to_vec())),
+        },
+        None => Err(TypeCmdError::Parse(tr!("parse.export_target"))),
+    }
+}
+
+pub(crate) fn parse_import_command(args: &[String]) -> Result<Command> {
+    match args.first().map(|source| source.to_lowercase()).as_deref() {
+        Some("json") => parse_import_json(&args[1..]),
+        Some("env") => parse_import_env(&args[1..]),
+        _ => Err(TypeCmdError::Parse(tr!("parse.import_target"))),
+    }
+}
+
+fn parse_import_env(args: &[String]) -> Result<Command> {
+    match args {
+        [] => Err(TypeCmdError::InsufficientArgs(tr!("args.import_env"))),
+        [all] if all == "--all" => Ok(Command::ImportEnv(None)),
+        names => match names.iter().find(|name| name.starts_with("--")) {
+            Some(flag) => Err(TypeCmdError::Parse(tr!("parse.unknown_option", flag))),
+            None => Ok(Command::ImportEnv(Some(names.to_vec()))),
+        },
+    }
+}
+
+fn parse_import_json(args: &[String]) -> Result<Command> {
+    let mut file = None;
+    let mut replace = false;
+    for arg in args {
+        match arg.as_str() {
+            "--merge" => replace = false,
+            "--replace" => replace = true,
+            flag if flag.starts_with("--") => {
+                return Err(TypeCmdError::Parse(tr!("parse.unknown_option", flag)));
+            }
+            _ if file.is_none() => file = Some(arg.clone()),
+            _ => return Err(TypeCmdError::Parse(tr!("parse.import_target"))),
+        }
+    }
+    
+    let file = file.ok_or_else(|| TypeCmdError::InsufficientArgs(tr!("args.import_file")))?;
+    Ok(Command::ImportJson(file, replace))
+}
+
+pub(crate) fn parse_run_command(args: &[String]) -> Result<Command> {
+    if args.is_empty() {
+        return Err(TypeCmdError::InsufficientArgs(tr!("args.run_program")));
+    }
+    
+    Ok(Command::Run(args.to_vec()))
+}
+
+pub(crate) fn parse_upper_command(_args: &[String]) -> Result<Command> {
+    Ok(Command::Filter(Filter::Upper))
+}
+
+pub(crate) fn parse_lower_command(_args: &[String]) -> Result<Command> {
+    Ok(Command::Filter(Filter::Lower))
+}
+
+pub(crate) fn parse_trim_command(_args: &[String]) -> Result<Command> {
+    Ok(Command::Filter(Filter::Trim))
+}
+
+pub(crate) fn parse_lines_command(_args: &[String]) -> Result<Command> {
+    Ok(Command::Filter(Filter::Lines))
+}
+
+pub(crate) fn parse_grep_command(args: &[String]) -> Result<Command> {
+    if args.is_empty() {
+        return Err(TypeCmdError::InsufficientArgs(tr!("args.grep_text")));
+    }
+    
+    Ok(Command::Filter(Filter::Grep(args.join(" "))))
+}
+
+pub(crate) fn parse_count_command(_args: &[String]) -> Result<Command> {
+    Ok(Command::Filter(Filter::Count))
+}
+
+pub(crate) fn parse_head_command(args: &[String]) -> Result<Command> {
+    Ok(Command::Filter(Filter::Head(parse_line_count(args)?)))
+}
+
+pub(crate) fn parse_tail_command(args: &[String]) -> Result<Command> {
+    Ok(Command::Filter(Filter::Tail(parse_line_count(args)?)))
+}
+
+fn parse_line_count(args: &[String]) -> Result<usize> {
+    match args.first() {
+        Some(text) => text
+            .parse()
+            .map_err(|_| TypeCmdError::Parse(tr!("parse.line_count", text))),
+        None => Ok(DEFAULT_LINES),
+    }
+}
+
+pub(crate) fn parse_env_command(args: &[String]) -> Result<Command> {
+    Ok(Command::Env(args.first().cloned()))
+}
+
+pub(crate) fn parse_show_command(args: &[String]) -> Result<Command> {
+    if args.is_empty() {
+        return Ok(Command::Show(ShowSubcommand::Help(None)));
     }
     
     match args[0].to_lowercase().as_str() {
-        "help" => Ok(Command::Show(ShowSubcommand::Help)),
+        "help" => Ok(Command::Show(ShowSubcommand::Help(args.get(1).cloned()))),
         "ver" | "version" => Ok(Command::Show(ShowSubcommand::Version)),
-        "vars" => Ok(Command::Show(ShowSubcommand::Variables)),
+        "vars" => match args.get(1).map(String::as_str) {
+            None => Ok(Command::Show(ShowSubcommand::Variables)),
+            Some("--scope") => Ok(Command::Show(ShowSubcommand::Scopes)),
+            Some(option) => Err(TypeCmdError::Parse(tr!("parse.unknown_option", option))),
+        },
         "history" => {
             if args.len() > 1 {
                 if let Ok(limit) = args[1].parse::<usize>() {
                     Ok(Command::Show(ShowSubcommand::History(Some(limit))))
                 } else {
-                    Err(TypeCmdError::Parse("无效的历史记录限制".to_string()))
+                    Err(TypeCmdError::Parse(tr!("parse.history_limit")))
                 }
             } else {
                 Ok(Command::Show(ShowSubcommand::History(None)))
             }
         }
         "lic" | "license" => Ok(Command::Show(ShowSubcommand::License)),
-        _ => Err(TypeCmdError::Parse(format!("未知的show子命令: {}", args[0]))),
+        "aliases" => Ok(Command::Show(ShowSubcommand::Aliases)),
+        "config" => Ok(Command::Show(ShowSubcommand::Config)),
+        _ => Err(TypeCmdError::Parse(tr!("parse.unknown_show", args[0]))),
     }
 }
 
-fn parse_exit_command(args: &[String]) -> Result<Command> {
+pub(crate) fn parse_exit_command(args: &[String]) -> Result<Command> {
     if args.is_empty() {
         Ok(Command::Exit(None))
     } else {
         match args[0].parse::<i32>() {
             Ok(code) => Ok(Command::Exit(Some(code))),
-            Err(_) => Err(TypeCmdError::Parse("无效的退出码".to_string())),
+            Err(_) => Err(TypeCmdError::Parse(tr!("parse.exit_code"))),
         }
     }
 }
 
-fn parse_set_command(args: &[String]) -> Result<Command> {
+pub(crate) fn parse_set_command(args: &[String]) -> Result<Command> {
+    if args.len() == 1 {
+        return Ok(Command::SetInput(args[0].clone()));
+    }
     if args.len() < 2 {
         return Err(TypeCmdError::InsufficientArgs(
-            "set命令需要至少2个参数".to_string(),
+            tr!("args.at_least_two", "set"),
         ));
     }
     
     let var_name = args[0].clone();
     let value = args[1..].join(" ");
-    Ok(Command::Set(var_name, value))
+    Ok(Command::Set(var_name, Value::Str(value)))
+}
+
+pub(crate) fn parse_local_command(args: &[String]) -> Result<Command> {
+    if args.len() < 2 {
+        return Err(TypeCmdError::InsufficientArgs(
+            tr!("args.at_least_two", "local"),
+        ));
+    }
+    
+    Ok(Command::Local(args[0].clone(), Value::Str(args[1..].join(" "))))
 }
 
-fn parse_iset_command(args: &[String]) -> Result<Command> {
+pub(crate) fn parse_const_command(args: &[String]) -> Result<Command> {
     if args.len() < 2 {
         return Err(TypeCmdError::InsufficientArgs(
-            "iset命令需要至少2个参数".to_string(),
+            tr!("args.at_least_two", "const"),
+        ));
+    }
+    
+    Ok(Command::ReadOnly(args[0].clone(), Some(Value::Str(args[1..].join(" ")))))
+}
+
+pub(crate) fn parse_readonly_command(args: &[String]) -> Result<Command> {
+    let Some(name) = args.first() else {
+        return Err(TypeCmdError::InsufficientArgs(tr!("args.variable_name", "readonly")));
+    };
+    
+    let value = (args.len() > 1).then(|| Value::Str(args[1..].join(" ")));
+    Ok(Command::ReadOnly(name.clone(), value))
+}
+
+pub(crate) fn parse_attr_command(args: &[String]) -> Result<Command> {
+    let Some(name) = args.first() else {
+        return Err(TypeCmdError::InsufficientArgs(tr!("args.variable_name", "attr")));
+    };
+    
+    let mut flags = Vec::new();
+    for arg in &args[1..] {
+        let flag = match (arg.strip_prefix('+'), arg.strip_prefix('-')) {
+            (Some(attr), _) => (attr.to_lowercase(), true),
+            (_, Some(attr)) => (attr.to_lowercase(), false),
+            _ => return Err(TypeCmdError::Parse(tr!("parse.attr_flag", arg))),
+        };
+        if !Attributes::NAMES.contains(&flag.0.as_str()) {
+            return Err(TypeCmdError::Parse(tr!("parse.attr_flag", arg)));
+        }
+        flags.push(flag);
+    }
+    Ok(Command::Attr(name.clone(), flags))
+}
+
+pub(crate) fn parse_iset_command(args: &[String]) -> Result<Command> {
+    if args.len() < 2 {
+        return Err(TypeCmdError::InsufficientArgs(
+            tr!("args.at_least_two", "iset"),
         ));
     }
     let varname = args[0].clone();
-    let value = args[1].clone();
+    let value = args[1..].join(" ");
     match value.parse::<i64>(){
-        Ok(num) => Ok(Command::ISet(varname, num)),
-        Err(_) => Err(TypeCmdError::Parse("无效的数字".to_string()))
+        Ok(num) => Ok(Command::Set(varname, Value::Int(num))),
+        Err(_) => Ok(Command::SetExpr(varname, value, NumericType::Int)),
+    }
+}
+
+pub(crate) fn parse_fset_command(args: &[String]) -> Result<Command> {
+    if args.len() < 2 {
+        return Err(TypeCmdError::InsufficientArgs(
+            tr!("args.at_least_two", "fset"),
+        ));
+    }
+    let value = args[1..].join(" ");
+    match value.parse::<f64>() {
+        Ok(num) => Ok(Command::Set(args[0].clone(), Value::Float(num))),
+        Err(_) => Ok(Command::SetExpr(args[0].clone(), value, NumericType::Float)),
+    }
+}
+
+pub(crate) fn parse_bset_command(args: &[String]) -> Result<Command> {
+    if args.len() < 2 {
+        return Err(TypeCmdError::InsufficientArgs(
+            tr!("args.at_least_two", "bset"),
+        ));
+    }
+    match Value::parse_bool(&args[1]) {
+        Some(b) => Ok(Command::Set(args[0].clone(), Value::Bool(b))),
+        None => Err(TypeCmdError::Parse(tr!("parse.bool"))),
     }
 }
 
-fn parse_get_command(args: &[String]) -> Result<Command> {
+pub(crate) fn parse_get_command(args: &[String]) -> Result<Command> {
     if args.is_empty() {
         return Err(TypeCmdError::InsufficientArgs(
-            "get命令需要变量名".to_string(),
+            tr!("args.variable_name", "get"),
         ));
     }
     
     Ok(Command::Get(args[0].clone()))
 }
 
-fn parse_string_command(args: &[String]) -> Result<Command> {
+pub(crate) fn parse_string_command(args: &[String]) -> Result<Command> {
     let text = if args.is_empty() {
         String::new()
     } else {
@@ -178,38 +754,45 @@ fn parse_string_command(args: &[String]) -> Result<Command> {
     Ok(Command::StringCmd(text))
 }
 
-fn parse_int_command(args: &[String]) -> Result<Command> {
+pub(crate) fn parse_int_command(args: &[String]) -> Result<Command> {
     if args.is_empty() {
         Ok(Command::IntCmd(None))
     } else {
-        match args[0].parse::<i32>() {
-            Ok(num) => Ok(Command::IntCmd(Some(num))),
-            Err(_) => Err(TypeCmdError::Parse("无效的数字".to_string())),
-        }
+        Ok(Command::IntCmd(Some(args.join(" "))))
     }
 }
 
-fn parse_delete_command(args: &[String]) -> Result<Command> {
+pub(crate) fn parse_calc_command(args: &[String]) -> Result<Command> {
     if args.is_empty() {
         return Err(TypeCmdError::InsufficientArgs(
-            "delete命令需要变量名".to_string(),
+            tr!("args.calc_expr"),
+        ));
+    }
+    
+    Ok(Command::Calc(args.join(" ")))
+}
+
+pub(crate) fn parse_delete_command(args: &[String]) -> Result<Command> {
+    if args.is_empty() {
+        return Err(TypeCmdError::InsufficientArgs(
+            tr!("args.variable_name", "delete"),
         ));
     }
     
     Ok(Command::Delete(args[0].clone()))
 }
 
-fn parse_clear_command(args: &[String]) -> Result<Command> {
+pub(crate) fn parse_clear_command(args: &[String]) -> Result<Command> {
     if args.is_empty() || args[0].to_lowercase() == "vars" {
         Ok(Command::Clear(ClearTarget::Variables))
     } else if args[0].to_lowercase() == "history" {
         Ok(Command::Clear(ClearTarget::History))
     } else {
-        Err(TypeCmdError::Parse("clear命令参数应为: vars 或 history".to_string()))
+        Err(TypeCmdError::Parse(tr!("parse.clear_target")))
     }
 }
 
-fn parse_history_command(args: &[String]) -> Result<Command> {
+pub(crate) fn parse_history_command(args: &[String]) -> Result<Command> {
     if args.is_empty() {
         Ok(Command::History(HistorySubcommand::List(None)))
     } else {
@@ -217,7 +800,7 @@ fn parse_history_command(args: &[String]) -> Result<Command> {
             "clear" => Ok(Command::History(HistorySubcommand::Clear)),
             "search" => {
                 if args.len() < 2 {
-                    Err(TypeCmdError::InsufficientArgs("搜索需要关键词".to_string()))
+                    Err(TypeCmdError::InsufficientArgs(tr!("args.search_keyword")))
                 } else {
                     Ok(Command::History(HistorySubcommand::Search(args[1].clone())))
                 }
@@ -226,9 +809,117 @@ fn parse_history_command(args: &[String]) -> Result<Command> {
                 if let Ok(limit) = args[0].parse::<usize>() {
                     Ok(Command::History(HistorySubcommand::List(Some(limit))))
                 } else {
-                    Err(TypeCmdError::Parse("无效的历史命令参数".to_string()))
+                    Err(TypeCmdError::Parse(tr!("parse.history_arg")))
                 }
             }
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segments(input: &str) -> Vec<(Connector, String)> {
+        split_chain(input).unwrap()
+    }
+
+    #[test]
+    fn chain_connectors() {
+        assert_eq!(segments("set a 1; get a && calc (a > 1 || b < 2) || list"), vec![
+            (Connector::Always, "set a 1".to_string()),
+            (Connector::Always, "get a".to_string()),
+            (Connector::And, "calc (a > 1 || b < 2)".to_string()),
+            (Connector::Or, "list".to_string()),
+        ]);
+        assert_eq!(segments(";; list ;"), vec![(Connector::Always, "list".to_string())]);
+    }
+
+    #[test]
+    fn chain_keeps_quoted_separators() {
+        assert_eq!(segments(r#"string "a;b" && string 'c||d'"#), vec![
+            (Connector::Always, r#"string "a;b""#.to_string()),
+            (Connector::And, "string 'c||d'".to_string()),
+        ]);
+        assert_eq!(segments(r"string a\;b").len(), 1);
+        // Backslash is literal inside single quotes, as in the tokenizer
+        assert_eq!(segments(r"string 'a\'; get x").len(), 2);
+    }
+
+    #[test]
+    fn chain_errors() {
+        assert!(matches!(split_chain("list &&"), Err(TypeCmdError::Parse(_))));
+        assert!(matches!(split_chain("|| list"), Err(TypeCmdError::Parse(_))));
+        assert!(matches!(split_chain("list && ; get a"), Err(TypeCmdError::Parse(_))));
+        assert!(matches!(split_chain(r#"string "a; b"#), Err(TypeCmdError::Parse(_))));
+    }
+
+    #[test]
+    fn tokenizer_quoting() {
+        assert_eq!(
+            parse_command(r#"set x "a b" 'c d' e\ f "g\"h""#).unwrap(),
+            vec!["set", "x", "a b", "c d", "e f", "g\"h"],
+        );
+        assert_eq!(parse_command(r"string 'a\b'").unwrap(), vec!["string", r"a\b"]);
+    }
+
+    #[test]
+    fn tokenizer_expands_references() {
+        let lookup = |name: &str| Ok(format!("<{}>", name));
+        assert_eq!(
+            parse_command_with(r#"get $x "${y}z" '$x' \$x"#, lookup).unwrap(),
+            vec!["get", "<x>", "<y>z", "$x", "$x"],
+        );
+    }
+
+    #[test]
+    fn pipeline_stages() {
+        assert_eq!(split_pipeline("get x | upper | count").unwrap(), vec!["get x", "upper", "count"]);
+        assert_eq!(split_pipeline("string 'a|b' | upper").unwrap(), vec!["string 'a|b'", "upper"]);
+        assert_eq!(split_pipeline("calc (1 | 2)").unwrap(), vec!["calc (1 | 2)"]);
+        assert_eq!(
+            split_pipeline("set x $(get a | upper) | count").unwrap(),
+            vec!["set x $(get a | upper)", "count"],
+        );
+        assert!(matches!(split_pipeline("get x |"), Err(TypeCmdError::Parse(_))));
+        assert!(matches!(split_pipeline("| upper"), Err(TypeCmdError::Parse(_))));
+        assert!(matches!(split_pipeline("get x | | upper"), Err(TypeCmdError::Parse(_))));
+    }
+
+    #[test]
+    fn substitutions_keep_their_quotes() {
+        // Quotes inside $(...) start fresh and do not end the outer quotes
+        assert_eq!(segments(r#"set x "$(run printf "a;b")"; get x"#), vec![
+            (Connector::Always, r#"set x "$(run printf "a;b")""#.to_string()),
+            (Connector::Always, "get x".to_string()),
+        ]);
+        assert_eq!(split_pipeline(r#"string "$(string "a|b")" | upper"#).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn substitution_expansion() {
+        let mut commands = Vec::new();
+        let expanded = expand_substitutions(r#"string $(get a) "$(string "a)b")" '$(no)'"#, |command| {
+            commands.push(command.to_string());
+            Ok("out\n".to_string())
+        }).unwrap();
+        assert_eq!(expanded, r#"string out "out" '$(no)'"#);
+        assert_eq!(commands, vec!["get a", r#"string "a)b""#]);
+
+        let escaped = expand_substitutions("string $(x)", |_| Ok(r#"a"b $c\d"#.to_string())).unwrap();
+        assert_eq!(escaped, r#"string a\"b \$c\\d"#);
+        assert_eq!(parse_command(&escaped).unwrap(), vec!["string", r#"a"b"#, r"$c\d"]);
+
+        assert!(matches!(expand_substitutions("string $(get a", |_| Ok(String::new())), Err(TypeCmdError::Parse(_))));
+    }
+
+    #[test]
+    fn export_arguments() {
+        let args = |line: &str| line.split_whitespace().map(str::to_string).collect::<Vec<_>>();
+        assert!(matches!(parse_export_command(&args("json")), Ok(Command::ExportJson(None))));
+        assert!(matches!(parse_export_command(&args("JSON out.json")), Ok(Command::ExportJson(Some(_)))));
+        assert!(matches!(parse_export_command(&args("a b")), Ok(Command::Export(names)) if names == ["a", "b"]));
+        assert!(matches!(parse_export_command(&args("a json")), Err(TypeCmdError::Parse(_))));
+        assert!(matches!(parse_export_command(&[]), Err(TypeCmdError::Parse(_))));
+    }
+}
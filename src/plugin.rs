@@ -0,0 +1,90 @@
+//! Extension point for host crates to register custom commands
+//!
+//! ```no_run
+//! use typecmd::prelude::*;
+//!
+//! let mut typecmd = TypeCmd::new()?;
+//! typecmd.register_command("greet", &["hi"], Box::new(
+//!     |args: &[String], ctx: &mut CommandContext| {
+//!         let msg = format!("你好, {}", args.join(" "));
+//!         ctx.output.info(&msg);
+//!         Ok(Some(msg))
+//!     },
+//! ))?;
+//! # Ok::<(), TypeCmdError>(())
+//! ```
+
+use crate::error::Result;
+use crate::history::HistoryManager;
+use crate::i18n;
+use crate::output::Output;
+use crate::tr;
+use crate::variables::VariableStore;
+
+/// State a custom command can read and modify
+pub struct CommandContext<'a> {
+    pub variables: &'a mut VariableStore,
+    pub history: &'a mut HistoryManager,
+    pub output: &'a dyn Output,
+}
+
+/// A custom command registered on `TypeCmd`
+pub trait CommandHandler {
+    /// Run the command with its arguments, already tokenized and with
+    /// `$name` references expanded
+    fn execute(&mut self, args: &[String], ctx: &mut CommandContext<'_>) -> Result<Option<String>>;
+    
+    /// One-line description shown in `show help`
+    fn summary(&self) -> &str {
+        i18n::text("plugin.summary")
+    }
+    
+    /// Argument synopsis shown in `help <command>`
+    fn usage(&self) -> &str {
+        ""
+    }
+}
+
+impl<F> CommandHandler for F
+where
+    F: FnMut(&[String], &mut CommandContext<'_>) -> Result<Option<String>>,
+{
+    fn execute(&mut self, args: &[String], ctx: &mut CommandContext<'_>) -> Result<Option<String>> {
+        self(args, ctx)
+    }
+}
+
+/// A registered custom command
+pub(crate) struct PluginCommand {
+    pub name: String,
+    pub aliases: Vec<String>,
+    pub handler: Box<dyn CommandHandler>,
+}
+
+impl PluginCommand {
+    /// Check whether `name` is this command's name or one of its aliases
+    pub fn matches(&self, name: &str) -> bool {
+        self.name == name || self.aliases.iter().any(|alias| alias == name)
+    }
+    
+    /// Name and aliases joined for display
+    pub fn names(&self) -> String {
+        std::iter::once(self.name.as_str())
+            .chain(self.aliases.iter().map(String::as_str))
+            .collect::<Vec<_>>()
+            .join(" | ")
+    }
+    
+    /// Detailed help for this command
+    pub fn help(&self) -> String {
+        let mut text = format!(
+            "{} - {}\n{}",
+            self.name, self.handler.summary(), tr!("help.usage", self.name, self.handler.usage())
+        );
+        if !self.aliases.is_empty() {
+            text.push('\n');
+            text.push_str(&tr!("help.aliases", self.aliases.join(", ")));
+        }
+        text
+    }
+}
@@ -0,0 +1,139 @@
+//! Prompt templates
+//!
+//! A template is plain text with `{placeholder}` fields, `$name` /
+//! `${name}` variable references, `$ENV{NAME}` environment references and
+//! `\n` line breaks. Placeholders:
+//!
+//! * `{version}` - TypeCmd version
+//! * `{vars}` / `{hist}` - number of variables / history entries
+//! * `{id}` - id the next history entry will get
+//! * `{status}` - exit status of the last command line
+//! * `{time}` - current time as `HH:MM:SS`
+//! * `{cwd}` - current directory, with the home directory shown as `~`
+//!
+//! Style markup switches the style of the text that follows until
+//! `{reset}`: a theme role such as `{prompt}` or `{muted}`, or any style
+//! accepted by `Style::parse`, such as `{red}`, `{bold+208}` or
+//! `{#ff8800}`. Unknown placeholders are kept as written,
+//! undefined variables expand to nothing and `\$`, `\{`, `\\` escape the
+//! special characters.
+
+use std::env;
+use std::path::Path;
+
+use chrono::Local;
+
+use crate::colors::{color_enabled, theme, RESET};
+use crate::history::HistoryManager;
+use crate::parser::{env_reference, read_reference};
+use crate::theme::{Role, Style};
+use crate::variables::VariableStore;
+
+/// Session state a prompt template can refer to
+pub struct PromptContext<'a> {
+    pub version: &'a str,
+    pub variables: &'a VariableStore,
+    pub history: &'a HistoryManager,
+    pub status: i32,
+}
+
+/// Render a prompt template
+pub fn render(template: &str, ctx: &PromptContext) -> String {
+    let mut out = String::new();
+    let mut styled = false;
+    let mut chars = template.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('n') => out.push('\n'),
+                Some(other) => out.push(other),
+                None => out.push('\\'),
+            },
+            '$' => match read_reference(&mut chars) {
+                Ok(Some(name)) => match env_reference(&name) {
+                    Some(var) => out.push_str(&env::var(var).unwrap_or_default()),
+                    None => {
+                        if let Some(value) = ctx.variables.get(&name) {
+                            out.push_str(&value.to_string());
+                        }
+                    }
+                },
+                _ => out.push('$'),
+            },
+            '{' => {
+                let mut name = String::new();
+                let mut closed = false;
+                for c in chars.by_ref() {
+                    if c == '}' {
+                        closed = true;
+                        break;
+                    }
+                    name.push(c);
+                }
+                if !closed {
+                    out.push('{');
+                    out.push_str(&name);
+                } else if let Some(text) = placeholder(&name, ctx) {
+                    out.push_str(&text);
+                } else if let Some(code) = markup(&name) {
+                    if color_enabled() {
+                        styled = code != RESET;
+                        out.push_str(&code);
+                    }
+                } else {
+                    out.push_str(&format!("{{{}}}", name));
+                }
+            }
+            _ => out.push(c),
+        }
+    }
+
+    // Keep the style from leaking into the typed command
+    if styled {
+        out.push_str(RESET);
+    }
+    out
+}
+
+fn placeholder(name: &str, ctx: &PromptContext) -> Option<String> {
+    Some(match name {
+        "version" => ctx.version.to_string(),
+        "vars" => ctx.variables.len().to_string(),
+        "hist" => ctx.history.count().to_string(),
+        "id" => ctx.history.next_id().to_string(),
+        "status" => ctx.status.to_string(),
+        "time" => Local::now().format("%H:%M:%S").to_string(),
+        "cwd" => current_dir(),
+        _ => return None,
+    })
+}
+
+/// Escape sequence for a style markup name
+fn markup(name: &str) -> Option<String> {
+    if name == "reset" {
+        return Some(RESET.to_string());
+    }
+    if let Some(role) = Role::from_name(name) {
+        return Some(theme().style(role).code().to_string());
+    }
+    if name.trim().is_empty() {
+        return None;
+    }
+    Style::parse(name).ok().map(|style| style.code().to_string())
+}
+
+fn current_dir() -> String {
+    let Ok(dir) = env::current_dir() else {
+        return "?".to_string();
+    };
+    if let Ok(home) = env::var("HOME")
+        && let Ok(rest) = dir.strip_prefix(Path::new(&home))
+    {
+        if rest.as_os_str().is_empty() {
+            return "~".to_string();
+        }
+        return Path::new("~").join(rest).display().to_string();
+    }
+    dir.display().to_string()
+}
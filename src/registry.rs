@@ -0,0 +1,571 @@
+//! Declarative table of built-in commands
+//!
+//! Each command declares its name, aliases, usage, help text, argument
+//! kinds, parser and handler. Parsing, dispatch, `show help`, per-command
+//! help and tab completion are all driven by this table.
+
+use crate::command::Command;
+use crate::executor::TypeCmd;
+use crate::config::CONFIG_KEYS;
+use crate::theme::THEME_NAMES;
+use crate::error::Result;
+use crate::i18n;
+use crate::tr;
+use crate::parser::{
+    parse_alias_command, parse_unalias_command, parse_config_command, parse_theme_command,
+    parse_bset_command, parse_calc_command, parse_clear_command, parse_copy_command,
+    parse_delete_command, parse_exit_command, parse_fset_command, parse_get_command,
+    parse_help_command, parse_history_command, parse_history_exec_command, parse_int_command,
+    parse_iset_command, parse_last_command, parse_list_command, parse_load_command, parse_local_command, parse_const_command,
+    parse_readonly_command, parse_attr_command,
+    parse_save_command, parse_export_command, parse_import_command, parse_env_command,
+    parse_run_command, parse_upper_command, parse_lower_command, parse_trim_command,
+    parse_lines_command, parse_grep_command, parse_count_command, parse_head_command,
+    parse_tail_command, parse_set_command, parse_show_command, parse_source_command, parse_string_command, parse_version_command,
+};
+
+/// Kind of a positional argument, used for completion
+#[derive(Debug, Clone, Copy)]
+pub enum ArgKind {
+    /// Name of an existing variable
+    Variable,
+    /// One of a fixed set of words
+    Values(&'static [&'static str]),
+    /// History entry id
+    HistoryId,
+    /// Name of a user-defined alias
+    Alias,
+    /// Command name
+    Command,
+    /// Free text, no completion
+    Text,
+}
+
+/// Declaration of a built-in command
+pub struct CommandSpec {
+    pub name: &'static str,
+    pub aliases: &'static [&'static str],
+    /// Message id of the argument synopsis shown after the name, empty for
+    /// commands without arguments
+    pub usage: &'static str,
+    /// Message id of the one-line description
+    pub summary: &'static str,
+    /// Positional argument kinds; the last one repeats
+    pub args: &'static [ArgKind],
+    /// Parse the arguments following the command name
+    pub parse: fn(&[String]) -> Result<Command>,
+    /// Run a command produced by `parse`
+    pub run: Handler,
+}
+
+/// Runs a parsed command on a session
+pub type Handler = fn(&mut TypeCmd, Command) -> Result<Option<String>>;
+
+/// Build a `Handler` from the `Command` variants a command's parser
+/// produces, e.g. `handler!(|typecmd| Command::Get(var) => typecmd.handle_get(&var))`
+macro_rules! handler {
+    (|$typecmd:ident| $($pattern:pat => $body:expr),+ $(,)?) => {
+        |$typecmd: &mut TypeCmd, command: Command| match command {
+            $($pattern => $body,)+
+            other => unreachable!("parser produced {:?}", other),
+        }
+    };
+}
+
+impl CommandSpec {
+    /// Check whether `name` is this command's name or one of its aliases
+    pub fn matches(&self, name: &str) -> bool {
+        self.name == name || self.aliases.contains(&name)
+    }
+
+    /// Kind of the argument at `index`
+    pub fn arg_kind(&self, index: usize) -> ArgKind {
+        self.args
+            .get(index)
+            .or(self.args.last())
+            .copied()
+            .unwrap_or(ArgKind::Text)
+    }
+
+    /// Name and aliases joined for display, e.g. `set | to | var | let`
+    pub fn names(&self) -> String {
+        std::iter::once(self.name)
+            .chain(self.aliases.iter().copied())
+            .collect::<Vec<_>>()
+            .join(" | ")
+    }
+
+    /// Argument synopsis in the current language
+    pub fn usage_text(&self) -> &'static str {
+        if self.usage.is_empty() { "" } else { i18n::text(self.usage) }
+    }
+
+    /// One-line description in the current language
+    pub fn summary_text(&self) -> &'static str {
+        i18n::text(self.summary)
+    }
+
+    /// Detailed help for this command
+    pub fn help(&self) -> String {
+        let mut text = format!(
+            "{} - {}\n{}",
+            self.name, self.summary_text(), tr!("help.usage", self.name, self.usage_text())
+        );
+        if !self.aliases.is_empty() {
+            text.push('\n');
+            text.push_str(&tr!("help.aliases", self.aliases.join(", ")));
+        }
+        text
+    }
+}
+
+/// Subcommands of `show`
+pub const SHOW_SUBCOMMANDS: &[&str] = &["help", "ver", "version", "vars", "history", "lic", "license", "aliases", "config"];
+
+/// Targets of `clear`
+pub const CLEAR_TARGETS: &[&str] = &["vars", "history"];
+
+/// Subcommands of `history`
+pub const HISTORY_SUBCOMMANDS: &[&str] = &["clear", "search"];
+
+/// Flags of `attr`
+pub const ATTRIBUTE_FLAGS: &[&str] = &[
+    "+readonly", "+exported", "-exported", "+integer", "-integer", "+hidden", "-hidden",
+];
+
+/// Targets of `save` and `load`
+pub const PERSIST_TARGETS: &[&str] = &["vars"];
+
+/// Sources of `import`
+pub const IMPORT_SOURCES: &[&str] = &["json", "env"];
+
+/// Options of `import`
+pub const IMPORT_OPTIONS: &[&str] = &["--merge", "--replace", "--all"];
+
+/// All built-in commands, in help order
+pub static BUILTIN_COMMANDS: &[CommandSpec] = &[
+    CommandSpec {
+        name: "show",
+        aliases: &[],
+        usage: "cmd.show.usage",
+        summary: "cmd.show.summary",
+        args: &[ArgKind::Values(SHOW_SUBCOMMANDS), ArgKind::Text],
+        parse: parse_show_command,
+        run: handler!(|typecmd| Command::Show(subcmd) => typecmd.handle_show(subcmd)),
+    },
+    CommandSpec {
+        name: "help",
+        aliases: &[],
+        usage: "cmd.help.usage",
+        summary: "cmd.help.summary",
+        args: &[ArgKind::Command],
+        parse: parse_help_command,
+        run: handler!(|typecmd| Command::Show(subcmd) => typecmd.handle_show(subcmd)),
+    },
+    CommandSpec {
+        name: "exit",
+        aliases: &["quit", "q"],
+        usage: "cmd.exit.usage",
+        summary: "cmd.exit.summary",
+        args: &[ArgKind::Text],
+        parse: parse_exit_command,
+        run: handler!(|typecmd| Command::Exit(code) => typecmd.handle_exit(code)),
+    },
+    CommandSpec {
+        name: "set",
+        aliases: &["to", "var", "let"],
+        usage: "cmd.set.usage",
+        summary: "cmd.set.summary",
+        args: &[ArgKind::Variable, ArgKind::Text],
+        parse: parse_set_command,
+        run: handler!(|typecmd|
+            Command::Set(var, value) => typecmd.handle_set(&var, value),
+            Command::SetInput(var) => typecmd.handle_set_input(&var),
+        ),
+    },
+    CommandSpec {
+        name: "iset",
+        aliases: &["ito", "ivar", "ilet"],
+        usage: "cmd.iset.usage",
+        summary: "cmd.iset.summary",
+        args: &[ArgKind::Variable, ArgKind::Variable],
+        parse: parse_iset_command,
+        run: handler!(|typecmd|
+            Command::Set(var, value) => typecmd.handle_set(&var, value),
+            Command::SetExpr(var, expr, ty) => typecmd.handle_set_expr(&var, &expr, ty),
+        ),
+    },
+    CommandSpec {
+        name: "fset",
+        aliases: &["fto", "fvar", "flet"],
+        usage: "cmd.fset.usage",
+        summary: "cmd.fset.summary",
+        args: &[ArgKind::Variable, ArgKind::Variable],
+        parse: parse_fset_command,
+        run: handler!(|typecmd|
+            Command::Set(var, value) => typecmd.handle_set(&var, value),
+            Command::SetExpr(var, expr, ty) => typecmd.handle_set_expr(&var, &expr, ty),
+        ),
+    },
+    CommandSpec {
+        name: "bset",
+        aliases: &["bto", "bvar", "blet"],
+        usage: "cmd.bset.usage",
+        summary: "cmd.bset.summary",
+        args: &[ArgKind::Variable, ArgKind::Values(&["true", "false"])],
+        parse: parse_bset_command,
+        run: handler!(|typecmd| Command::Set(var, value) => typecmd.handle_set(&var, value)),
+    },
+    CommandSpec {
+        name: "local",
+        aliases: &[],
+        usage: "cmd.local.usage",
+        summary: "cmd.local.summary",
+        args: &[ArgKind::Variable, ArgKind::Text],
+        parse: parse_local_command,
+        run: handler!(|typecmd| Command::Local(var, value) => typecmd.handle_local(&var, value)),
+    },
+    CommandSpec {
+        name: "const",
+        aliases: &[],
+        usage: "cmd.const.usage",
+        summary: "cmd.const.summary",
+        args: &[ArgKind::Variable, ArgKind::Text],
+        parse: parse_const_command,
+        run: handler!(|typecmd| Command::ReadOnly(var, value) => typecmd.handle_readonly(&var, value)),
+    },
+    CommandSpec {
+        name: "readonly",
+        aliases: &[],
+        usage: "cmd.readonly.usage",
+        summary: "cmd.readonly.summary",
+        args: &[ArgKind::Variable, ArgKind::Text],
+        parse: parse_readonly_command,
+        run: handler!(|typecmd| Command::ReadOnly(var, value) => typecmd.handle_readonly(&var, value)),
+    },
+    CommandSpec {
+        name: "attr",
+        aliases: &[],
+        usage: "cmd.attr.usage",
+        summary: "cmd.attr.summary",
+        args: &[ArgKind::Variable, ArgKind::Values(ATTRIBUTE_FLAGS)],
+        parse: parse_attr_command,
+        run: handler!(|typecmd| Command::Attr(var, flags) => typecmd.handle_attr(&var, &flags)),
+    },
+    CommandSpec {
+        name: "get",
+        aliases: &["which", "echo"],
+        usage: "cmd.get.usage",
+        summary: "cmd.get.summary",
+        args: &[ArgKind::Variable],
+        parse: parse_get_command,
+        run: handler!(|typecmd| Command::Get(var) => typecmd.handle_get(&var)),
+    },
+    CommandSpec {
+        name: "copy",
+        aliases: &["cpvar"],
+        usage: "cmd.copy.usage",
+        summary: "cmd.copy.summary",
+        args: &[ArgKind::Variable, ArgKind::Variable],
+        parse: parse_copy_command,
+        run: handler!(|typecmd| Command::Copy(var, oldvar, extra) => typecmd.handle_copy(&var, &oldvar, extra)),
+    },
+    CommandSpec {
+        name: "string",
+        aliases: &["str", "sprint"],
+        usage: "cmd.string.usage",
+        summary: "cmd.string.summary",
+        args: &[ArgKind::Text],
+        parse: parse_string_command,
+        run: handler!(|typecmd| Command::StringCmd(text) => typecmd.handle_string(&text)),
+    },
+    CommandSpec {
+        name: "int",
+        aliases: &["num"],
+        usage: "cmd.int.usage",
+        summary: "cmd.int.summary",
+        args: &[ArgKind::Variable],
+        parse: parse_int_command,
+        run: handler!(|typecmd| Command::IntCmd(expr) => typecmd.handle_int(expr.as_deref())),
+    },
+    CommandSpec {
+        name: "calc",
+        aliases: &[],
+        usage: "cmd.calc.usage",
+        summary: "cmd.calc.summary",
+        args: &[ArgKind::Variable],
+        parse: parse_calc_command,
+        run: handler!(|typecmd| Command::Calc(expr) => typecmd.handle_calc(&expr)),
+    },
+    CommandSpec {
+        name: "list",
+        aliases: &["ls"],
+        usage: "",
+        summary: "cmd.list.summary",
+        args: &[],
+        parse: parse_list_command,
+        run: handler!(|typecmd| Command::List => typecmd.handle_list()),
+    },
+    CommandSpec {
+        name: "rm",
+        aliases: &["del", "unset"],
+        usage: "cmd.rm.usage",
+        summary: "cmd.rm.summary",
+        args: &[ArgKind::Variable, ArgKind::Text],
+        parse: parse_delete_command,
+        run: handler!(|typecmd| Command::Delete(var) => typecmd.handle_delete(&var)),
+    },
+    CommandSpec {
+        name: "clear",
+        aliases: &["cls"],
+        usage: "cmd.clear.usage",
+        summary: "cmd.clear.summary",
+        args: &[ArgKind::Values(CLEAR_TARGETS), ArgKind::Text],
+        parse: parse_clear_command,
+        run: handler!(|typecmd| Command::Clear(target) => typecmd.handle_clear(target)),
+    },
+    CommandSpec {
+        name: "history",
+        aliases: &["hist"],
+        usage: "cmd.history.usage",
+        summary: "cmd.history.summary",
+        args: &[ArgKind::Values(HISTORY_SUBCOMMANDS), ArgKind::Text],
+        parse: parse_history_command,
+        run: handler!(|typecmd| Command::History(subcmd) => typecmd.handle_history(subcmd)),
+    },
+    CommandSpec {
+        name: "version",
+        aliases: &["ver"],
+        usage: "",
+        summary: "cmd.version.summary",
+        args: &[],
+        parse: parse_version_command,
+        run: handler!(|typecmd| Command::Show(subcmd) => typecmd.handle_show(subcmd)),
+    },
+    CommandSpec {
+        name: "source",
+        aliases: &["."],
+        usage: "cmd.source.usage",
+        summary: "cmd.source.summary",
+        args: &[ArgKind::Text],
+        parse: parse_source_command,
+        run: handler!(|typecmd| Command::Source(file) => typecmd.handle_source(&file)),
+    },
+    CommandSpec {
+        name: "alias",
+        aliases: &[],
+        usage: "cmd.alias.usage",
+        summary: "cmd.alias.summary",
+        args: &[ArgKind::Alias],
+        parse: parse_alias_command,
+        run: handler!(|typecmd| Command::Alias(subcmd) => typecmd.handle_alias(subcmd)),
+    },
+    CommandSpec {
+        name: "unalias",
+        aliases: &[],
+        usage: "cmd.unalias.usage",
+        summary: "cmd.unalias.summary",
+        args: &[ArgKind::Alias],
+        parse: parse_unalias_command,
+        run: handler!(|typecmd| Command::Alias(subcmd) => typecmd.handle_alias(subcmd)),
+    },
+    CommandSpec {
+        name: "config",
+        aliases: &[],
+        usage: "cmd.config.usage",
+        summary: "cmd.config.summary",
+        args: &[ArgKind::Values(CONFIG_KEYS), ArgKind::Text],
+        parse: parse_config_command,
+        run: handler!(|typecmd|
+            Command::Config(key, value) => typecmd.handle_config(&key, &value),
+            Command::Show(subcmd) => typecmd.handle_show(subcmd),
+        ),
+    },
+    CommandSpec {
+        name: "theme",
+        aliases: &[],
+        usage: "cmd.theme.usage",
+        summary: "cmd.theme.summary",
+        args: &[ArgKind::Values(THEME_NAMES)],
+        parse: parse_theme_command,
+        run: handler!(|typecmd| Command::Theme(name) => typecmd.handle_theme(name.as_deref())),
+    },
+    CommandSpec {
+        name: "save",
+        aliases: &[],
+        usage: "cmd.save.usage",
+        summary: "cmd.save.summary",
+        args: &[ArgKind::Values(PERSIST_TARGETS), ArgKind::Text],
+        parse: parse_save_command,
+        run: handler!(|typecmd| Command::SaveVars(file) => typecmd.handle_save_vars(file.as_deref())),
+    },
+    CommandSpec {
+        name: "load",
+        aliases: &[],
+        usage: "cmd.load.usage",
+        summary: "cmd.load.summary",
+        args: &[ArgKind::Values(PERSIST_TARGETS), ArgKind::Text],
+        parse: parse_load_command,
+        run: handler!(|typecmd| Command::LoadVars(file) => typecmd.handle_load_vars(file.as_deref())),
+    },
+    CommandSpec {
+        name: "export",
+        aliases: &[],
+        usage: "cmd.export.usage",
+        summary: "cmd.export.summary",
+        args: &[ArgKind::Variable],
+        parse: parse_export_command,
+        run: handler!(|typecmd|
+            Command::ExportJson(file) => typecmd.handle_export_json(file.as_deref()),
+            Command::Export(names) => typecmd.handle_export(&names),
+        ),
+    },
+    CommandSpec {
+        name: "import",
+        aliases: &[],
+        usage: "cmd.import.usage",
+        summary: "cmd.import.summary",
+        args: &[ArgKind::Values(IMPORT_SOURCES), ArgKind::Text, ArgKind::Values(IMPORT_OPTIONS)],
+        parse: parse_import_command,
+        run: handler!(|typecmd|
+            Command::ImportJson(file, replace) => typecmd.handle_import_json(&file, replace),
+            Command::ImportEnv(names) => typecmd.handle_import_env(names),
+        ),
+    },
+    CommandSpec {
+        name: "env",
+        aliases: &[],
+        usage: "cmd.env.usage",
+        summary: "cmd.env.summary",
+        args: &[ArgKind::Text],
+        parse: parse_env_command,
+        run: handler!(|typecmd| Command::Env(name) => typecmd.handle_env(name.as_deref())),
+    },
+    CommandSpec {
+        name: "run",
+        aliases: &[],
+        usage: "cmd.run.usage",
+        summary: "cmd.run.summary",
+        args: &[ArgKind::Text],
+        parse: parse_run_command,
+        run: handler!(|typecmd| Command::Run(args) => typecmd.run_program(&args)),
+    },
+    CommandSpec {
+        name: "upper",
+        aliases: &[],
+        usage: "",
+        summary: "cmd.upper.summary",
+        args: &[],
+        parse: parse_upper_command,
+        run: handler!(|typecmd| Command::Filter(filter) => typecmd.handle_filter(&filter)),
+    },
+    CommandSpec {
+        name: "lower",
+        aliases: &[],
+        usage: "",
+        summary: "cmd.lower.summary",
+        args: &[],
+        parse: parse_lower_command,
+        run: handler!(|typecmd| Command::Filter(filter) => typecmd.handle_filter(&filter)),
+    },
+    CommandSpec {
+        name: "trim",
+        aliases: &[],
+        usage: "",
+        summary: "cmd.trim.summary",
+        args: &[],
+        parse: parse_trim_command,
+        run: handler!(|typecmd| Command::Filter(filter) => typecmd.handle_filter(&filter)),
+    },
+    CommandSpec {
+        name: "lines",
+        aliases: &[],
+        usage: "",
+        summary: "cmd.lines.summary",
+        args: &[],
+        parse: parse_lines_command,
+        run: handler!(|typecmd| Command::Filter(filter) => typecmd.handle_filter(&filter)),
+    },
+    CommandSpec {
+        name: "grep",
+        aliases: &[],
+        usage: "cmd.grep.usage",
+        summary: "cmd.grep.summary",
+        args: &[ArgKind::Text],
+        parse: parse_grep_command,
+        run: handler!(|typecmd| Command::Filter(filter) => typecmd.handle_filter(&filter)),
+    },
+    CommandSpec {
+        name: "count",
+        aliases: &[],
+        usage: "",
+        summary: "cmd.count.summary",
+        args: &[],
+        parse: parse_count_command,
+        run: handler!(|typecmd| Command::Filter(filter) => typecmd.handle_filter(&filter)),
+    },
+    CommandSpec {
+        name: "head",
+        aliases: &[],
+        usage: "cmd.head.usage",
+        summary: "cmd.head.summary",
+        args: &[ArgKind::Text],
+        parse: parse_head_command,
+        run: handler!(|typecmd| Command::Filter(filter) => typecmd.handle_filter(&filter)),
+    },
+    CommandSpec {
+        name: "tail",
+        aliases: &[],
+        usage: "cmd.tail.usage",
+        summary: "cmd.tail.summary",
+        args: &[ArgKind::Text],
+        parse: parse_tail_command,
+        run: handler!(|typecmd| Command::Filter(filter) => typecmd.handle_filter(&filter)),
+    },
+    CommandSpec {
+        name: "!!",
+        aliases: &[],
+        usage: "",
+        summary: "cmd.last.summary",
+        args: &[],
+        parse: parse_last_command,
+        run: handler!(|typecmd| Command::LastCommand => typecmd.handle_last_command()),
+    },
+    CommandSpec {
+        name: "!",
+        aliases: &[],
+        usage: "cmd.history_exec.usage",
+        summary: "cmd.history_exec.summary",
+        args: &[ArgKind::HistoryId, ArgKind::Text],
+        parse: parse_history_exec_command,
+        run: handler!(|typecmd| Command::HistoryCommand(spec) => typecmd.handle_history_command(&spec)),
+    },
+];
+
+/// Find a built-in command by name or alias (case-insensitive)
+pub fn find_command(name: &str) -> Option<&'static CommandSpec> {
+    let name = name.to_lowercase();
+    BUILTIN_COMMANDS.iter().find(|spec| spec.matches(&name))
+}
+
+/// All command names and aliases
+pub fn command_names() -> impl Iterator<Item = &'static str> {
+    BUILTIN_COMMANDS
+        .iter()
+        .flat_map(|spec| std::iter::once(spec.name).chain(spec.aliases.iter().copied()))
+}
+
+/// One `(names, description)` help row per command
+pub fn help_entries() -> Vec<(String, String)> {
+    BUILTIN_COMMANDS
+        .iter()
+        .map(|spec| {
+            let description = if spec.usage.is_empty() {
+                spec.summary_text().to_string()
+            } else {
+                format!("{}: {} {}", spec.summary_text(), spec.name, spec.usage_text())
+            };
+            (spec.names(), description)
+        })
+        .collect()
+}
@@ -3,6 +3,8 @@
 
 use crate::error::Result;
 use crate::executor::TypeCmd;
+use crate::output::Output;
+use crate::plugin::CommandHandler;
 
 /// REPL runner for TypeCmd
 pub struct Repl {
@@ -17,8 +19,15 @@ impl Repl {
         })
     }
     
-    /// Run the REPL
-    pub fn run(&mut self) -> Result<()> {
+    /// Create a new REPL instance writing messages to `output`
+    pub fn with_output(output: Box<dyn Output>) -> Result<Self> {
+        let mut typecmd = TypeCmd::new()?;
+        typecmd.set_output(output);
+        Ok(Repl { typecmd })
+    }
+    
+    /// Run the REPL, returning the exit code requested by `exit`
+    pub fn run(&mut self) -> Result<i32> {
         self.typecmd.run()
     }
     
@@ -27,6 +36,16 @@ impl Repl {
         self.typecmd.execute_command(command)
     }
     
+    /// Register a custom command under `name` and `aliases`
+    pub fn register_command(
+        &mut self,
+        name: &str,
+        aliases: &[&str],
+        handler: Box<dyn CommandHandler>,
+    ) -> Result<()> {
+        self.typecmd.register_command(name, aliases, handler)
+    }
+    
     /// Get a reference to the underlying TypeCmd instance
     pub fn typecmd(&self) -> &TypeCmd {
         &self.typecmd
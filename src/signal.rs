@@ -0,0 +1,45 @@
+//! SIGINT handling for the interactive prompt
+//!
+//! The handler only records that Ctrl-C was pressed; the REPL polls the flag
+//! to cancel the current input line instead of letting the process die.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+#[cfg(unix)]
+extern "C" fn on_sigint(_signal: libc::c_int) {
+    INTERRUPTED.store(true, Ordering::SeqCst);
+}
+
+/// Install the SIGINT handler.
+///
+/// The handler is installed without `SA_RESTART`, so a blocking read of
+/// stdin fails with `ErrorKind::Interrupted` when Ctrl-C is pressed.
+#[cfg(unix)]
+pub fn install_sigint_handler() {
+    // SAFETY: the handler only touches an atomic, which is async-signal-safe,
+    // and the sigaction struct is fully initialized before use.
+    unsafe {
+        let mut action: libc::sigaction = std::mem::zeroed();
+        action.sa_sigaction = on_sigint as *const () as libc::sighandler_t;
+        action.sa_flags = 0;
+        libc::sigemptyset(&mut action.sa_mask);
+        libc::sigaction(libc::SIGINT, &action, std::ptr::null_mut());
+    }
+}
+
+/// Install the SIGINT handler (no-op on this platform)
+#[cfg(not(unix))]
+pub fn install_sigint_handler() {}
+
+/// Check and reset the interrupted flag
+pub fn take_interrupt() -> bool {
+    INTERRUPTED.swap(false, Ordering::SeqCst)
+}
+
+/// Forget an earlier Ctrl-C, such as one that stopped a child process, so
+/// that only interrupts arriving from now on count
+pub fn clear_interrupt() {
+    INTERRUPTED.store(false, Ordering::SeqCst);
+}
@@ -0,0 +1,172 @@
+//! Color themes
+//!
+//! A theme maps semantic roles such as `error` or `prompt` to styles. A
+//! style is written as `+`-separated parts, each one an attribute (`bold`,
+//! `dim`, `italic`, `underline`), a color name (`red`, `cyan`, ...), a
+//! 256-color index (`208`) or a truecolor value (`#ff8800`), e.g.
+//! `bold+#ff8800`. `none` is a style without any effect.
+
+use std::fmt;
+
+use crate::error::{TypeCmdError, Result};
+use crate::tr;
+
+/// Semantic output role
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Error,
+    Success,
+    Info,
+    Warn,
+    Muted,
+    Prompt,
+}
+
+impl Role {
+    /// All roles, in display order
+    pub const ALL: [Role; 6] = [
+        Role::Error, Role::Success, Role::Info, Role::Warn, Role::Muted, Role::Prompt,
+    ];
+
+    /// Name used in configuration and prompt markup
+    pub fn name(self) -> &'static str {
+        match self {
+            Role::Error => "error",
+            Role::Success => "success",
+            Role::Info => "info",
+            Role::Warn => "warn",
+            Role::Muted => "muted",
+            Role::Prompt => "prompt",
+        }
+    }
+
+    /// Find a role by name
+    pub fn from_name(name: &str) -> Option<Role> {
+        Role::ALL.into_iter().find(|role| role.name() == name)
+    }
+}
+
+/// Named colors and their SGR foreground codes
+const COLOR_NAMES: &[(&str, &str)] = &[
+    ("black", "30"),
+    ("red", "31"),
+    ("green", "32"),
+    ("yellow", "33"),
+    ("blue", "34"),
+    ("purple", "35"),
+    ("magenta", "35"),
+    ("cyan", "36"),
+    ("white", "37"),
+    ("gray", "90"),
+];
+
+/// Text attributes and their SGR codes
+const ATTRIBUTES: &[(&str, &str)] = &[
+    ("bold", "1"),
+    ("dim", "2"),
+    ("italic", "3"),
+    ("underline", "4"),
+];
+
+/// A parsed style, keeping the text it was written as
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Style {
+    spec: String,
+    code: String,
+}
+
+impl Style {
+    /// Parse a style such as `bold+#ff8800`
+    pub fn parse(spec: &str) -> Result<Self> {
+        let spec = spec.trim().to_lowercase();
+        let mut params = Vec::new();
+
+        for part in spec.split('+').map(str::trim) {
+            if part.is_empty() || part == "none" {
+                continue;
+            }
+            let named = COLOR_NAMES.iter().chain(ATTRIBUTES).find(|(name, _)| *name == part);
+            if let Some((_, code)) = named {
+                params.push(code.to_string());
+            } else if let Ok(index) = part.parse::<u8>() {
+                params.push(format!("38;5;{}", index));
+            } else if let Some((r, g, b)) = parse_hex(part) {
+                params.push(format!("38;2;{};{};{}", r, g, b));
+            } else {
+                return Err(TypeCmdError::Parse(tr!("theme.invalid_color", part)));
+            }
+        }
+
+        let code = if params.is_empty() {
+            String::new()
+        } else {
+            format!("\x1b[{}m", params.join(";"))
+        };
+        Ok(Style { spec, code })
+    }
+
+    /// Escape sequence that switches to this style, empty for `none`
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+}
+
+impl fmt::Display for Style {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.spec.is_empty() {
+            write!(f, "none")
+        } else {
+            write!(f, "{}", self.spec)
+        }
+    }
+}
+
+fn parse_hex(text: &str) -> Option<(u8, u8, u8)> {
+    let hex = text.strip_prefix('#')?;
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some((channel(0)?, channel(2)?, channel(4)?))
+}
+
+/// Names of the built-in themes
+pub const THEME_NAMES: &[&str] = &["dark", "light", "mono"];
+
+/// Styles for every role
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Theme {
+    pub name: String,
+    styles: [Style; 6],
+}
+
+impl Theme {
+    /// Built-in theme by name
+    pub fn builtin(name: &str) -> Option<Theme> {
+        // error, success, info, warn, muted, prompt
+        let specs = match name {
+            "dark" => ["red", "green", "blue", "yellow", "gray", "purple"],
+            "light" => ["160", "28", "25", "130", "244", "90"],
+            "mono" => ["bold", "none", "none", "bold", "dim", "bold"],
+            _ => return None,
+        };
+        let styles = specs.map(|spec| Style::parse(spec).expect("built-in styles are valid"));
+        Some(Theme { name: name.to_string(), styles })
+    }
+
+    /// Style for a role
+    pub fn style(&self, role: Role) -> &Style {
+        &self.styles[role as usize]
+    }
+
+    /// Override the style of one role
+    pub fn set_style(&mut self, role: Role, style: Style) {
+        self.styles[role as usize] = style;
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::builtin("dark").expect("dark theme exists")
+    }
+}
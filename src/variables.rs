@@ -1,56 +1,671 @@
 use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::error::{TypeCmdError, Result};
+use crate::tr;
+use crate::value::Value;
+
+/// Default variable file name, stored next to the history file
+pub const VARS_FILE: &str = ".typecmd_vars";
+
+/// Per-variable attributes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Attributes {
+    /// The variable cannot be changed or deleted
+    pub readonly: bool,
+    /// The variable is passed to child processes
+    pub exported: bool,
+    /// Values are converted to integers; other values are rejected
+    pub integer: bool,
+    /// The variable is left out of variable listings
+    pub hidden: bool,
+}
+
+impl Attributes {
+    /// Attribute names, in display order
+    pub const NAMES: [&'static str; 4] = ["readonly", "exported", "integer", "hidden"];
+
+    /// Flag of an attribute by name
+    pub fn flag_mut(&mut self, name: &str) -> Option<&mut bool> {
+        match name {
+            "readonly" => Some(&mut self.readonly),
+            "exported" => Some(&mut self.exported),
+            "integer" => Some(&mut self.integer),
+            "hidden" => Some(&mut self.hidden),
+            _ => None,
+        }
+    }
+
+    /// Names of the attributes that are set
+    pub fn names(&self) -> Vec<&'static str> {
+        let flags = [self.readonly, self.exported, self.integer, self.hidden];
+        Self::NAMES.into_iter().zip(flags).filter(|(_, set)| *set).map(|(name, _)| name).collect()
+    }
+
+    /// Check that a variable with these attributes may take `value`,
+    /// converting it to an integer for `integer` variables
+    fn admit(&self, key: &str, value: Value) -> Result<Value> {
+        if self.readonly {
+            return Err(TypeCmdError::ReadOnly(key.to_string()));
+        }
+        if !self.integer {
+            return Ok(value);
+        }
+        match value {
+            Value::Int(n) => Ok(Value::Int(n)),
+            Value::Str(text) if text.trim().parse::<i64>().is_ok() => {
+                Ok(Value::Int(text.trim().parse().expect("checked above")))
+            }
+            other => Err(TypeCmdError::TypeMismatch(
+                tr!("vars.integer_only", key, other.type_name())
+            )),
+        }
+    }
+}
+
+/// A stored value and its attributes
+#[derive(Debug, Clone)]
+struct Entry {
+    value: Value,
+    attrs: Attributes,
+}
+
+impl Entry {
+    fn new(value: Value) -> Self {
+        Entry { value, attrs: Attributes::default() }
+    }
+}
+
+/// Kind of a variable scope
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScopeKind {
+    /// The outermost scope, always present
+    Global,
+    /// An interactive session, `-c` command or stdin run
+    Session,
+    /// A script run with `source`
+    Script,
+    /// A block or function body
+    Block,
+}
+
+impl ScopeKind {
+    /// Message id of the scope name
+    fn label(self) -> &'static str {
+        match self {
+            ScopeKind::Global => "vars.scope.global",
+            ScopeKind::Session => "vars.scope.session",
+            ScopeKind::Script => "vars.scope.script",
+            ScopeKind::Block => "vars.scope.block",
+        }
+    }
+
+    /// Localized scope name
+    pub fn name(self) -> &'static str {
+        crate::i18n::text(self.label())
+    }
+}
+
+/// Variables defined in one scope
+#[derive(Debug, Clone)]
+pub struct Scope {
+    pub kind: ScopeKind,
+    vars: HashMap<String, Entry>,
+}
+
+impl Scope {
+    fn new(kind: ScopeKind) -> Self {
+        Scope { kind, vars: HashMap::new() }
+    }
+
+    /// Variables of this scope that are not hidden, sorted by name
+    pub fn listed(&self) -> Vec<(&String, &Value, Attributes)> {
+        let mut vars: Vec<_> = self.vars.iter()
+            .filter(|(_, entry)| !entry.attrs.hidden)
+            .map(|(name, entry)| (name, &entry.value, entry.attrs))
+            .collect();
+        vars.sort_by(|a, b| a.0.cmp(b.0));
+        vars
+    }
+
+    /// Number of variables in this scope
+    pub fn len(&self) -> usize {
+        self.vars.len()
+    }
+
+    /// Check if the scope has no variables
+    pub fn is_empty(&self) -> bool {
+        self.vars.is_empty()
+    }
+
+    /// Set `key` in this scope, respecting the attributes of an existing
+    /// variable
+    fn set(&mut self, key: String, value: Value) -> Result<()> {
+        match self.vars.get_mut(&key) {
+            Some(entry) => entry.value = entry.attrs.admit(&key, value)?,
+            None => {
+                self.vars.insert(key, Entry::new(value));
+            }
+        }
+        Ok(())
+    }
+}
 
 /// Variable storage manager
-#[derive(Debug, Clone, Default)]
+///
+/// Variables live in a stack of scopes, innermost last. Lookup walks from
+/// the innermost scope outward. `set` changes the nearest existing variable
+/// or creates a global one; `set_local` always defines the variable in the
+/// innermost scope, hiding outer variables of the same name until that
+/// scope is popped. Every variable carries `Attributes`; readonly variables
+/// reject changes with `TypeCmdError::ReadOnly`.
+#[derive(Debug, Clone)]
 pub struct VariableStore {
-    store: HashMap<String, String>,
+    scopes: Vec<Scope>,
+    /// Incremented on every change, so callers can tell whether the store
+    /// changed since they last looked
+    revision: u64,
+}
+
+impl Default for VariableStore {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl VariableStore {
-    /// Create a new empty variable store
+    /// Create a new store with an empty global scope
     pub fn new() -> Self {
         VariableStore {
-            store: HashMap::new(),
+            scopes: vec![Scope::new(ScopeKind::Global)],
+            revision: 0,
         }
     }
-    
+
+    /// Innermost scope holding `key`
+    fn scope_of(&self, key: &str) -> Option<usize> {
+        self.scopes.iter().rposition(|scope| scope.vars.contains_key(key))
+    }
+
+    fn entry(&self, key: &str) -> Option<&Entry> {
+        self.scope_of(key).and_then(|index| self.scopes[index].vars.get(key))
+    }
+
     /// Get a variable value
-    pub fn get(&self, key: &str) -> Option<&String> {
-        self.store.get(key)
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.entry(key).map(|entry| &entry.value)
+    }
+
+    /// Get the attributes of a variable
+    pub fn attributes(&self, key: &str) -> Option<Attributes> {
+        self.entry(key).map(|entry| entry.attrs)
+    }
+
+    /// Set a variable value, changing the nearest existing variable or
+    /// creating a global one
+    pub fn set(&mut self, key: String, value: Value) -> Result<()> {
+        let index = self.scope_of(&key).unwrap_or(0);
+        self.scopes[index].set(key, value)?;
+        self.revision += 1;
+        Ok(())
+    }
+
+    /// Define a variable in the innermost scope
+    pub fn set_local(&mut self, key: String, value: Value) -> Result<()> {
+        let scope = self.scopes.last_mut().expect("global scope is never popped");
+        scope.set(key, value)?;
+        self.revision += 1;
+        Ok(())
+    }
+
+    /// Set the nearest variable named `key`, or a new global one, together
+    /// with its attributes, as when restoring saved variables. An existing
+    /// readonly variable cannot be changed, unless `value` and `attrs` are
+    /// what it already holds.
+    pub fn define(&mut self, key: String, value: Value, attrs: Attributes) -> Result<()> {
+        let index = self.scope_of(&key).unwrap_or(0);
+        let scope = &mut self.scopes[index];
+        let check = Attributes { readonly: false, ..attrs };
+        let value = check.admit(&key, value)?;
+        if let Some(entry) = scope.vars.get(&key)
+            && entry.attrs.readonly
+        {
+            if entry.value == value && entry.attrs == attrs {
+                return Ok(());
+            }
+            return Err(TypeCmdError::ReadOnly(key));
+        }
+        scope.vars.insert(key, Entry { value, attrs });
+        self.revision += 1;
+        Ok(())
+    }
+
+    /// Replace the attributes of the nearest variable named `key`. The
+    /// readonly attribute cannot be removed, and turning on `integer`
+    /// converts the current value.
+    pub fn set_attributes(&mut self, key: &str, attrs: Attributes) -> Result<()> {
+        let index = self.scope_of(key)
+            .ok_or_else(|| TypeCmdError::UndefinedVariable(key.to_string()))?;
+        let entry = self.scopes[index].vars.get_mut(key).expect("scope holds the variable");
+        if entry.attrs.readonly && !attrs.readonly {
+            return Err(TypeCmdError::ReadOnly(key.to_string()));
+        }
+        if attrs.integer && !entry.attrs.integer {
+            let check = Attributes { integer: true, ..Attributes::default() };
+            entry.value = check.admit(key, entry.value.clone())?;
+        }
+        entry.attrs = attrs;
+        self.revision += 1;
+        Ok(())
     }
-    
-    /// Set a variable value
-    pub fn set(&mut self, key: String, value: String) {
-        self.store.insert(key, value);
+
+    /// Mark the nearest variable named `key` as exported
+    pub fn export(&mut self, key: &str) -> Result<()> {
+        let mut attrs = self.attributes(key)
+            .ok_or_else(|| TypeCmdError::UndefinedVariable(key.to_string()))?;
+        attrs.exported = true;
+        self.set_attributes(key, attrs)
     }
-    
+
     /// Check if a variable exists
     pub fn has(&self, key: &str) -> bool {
-        self.store.contains_key(key)
+        self.scope_of(key).is_some()
+    }
+
+    /// Delete the nearest variable named `key`
+    pub fn delete(&mut self, key: &str) -> Result<bool> {
+        let Some(index) = self.scope_of(key) else {
+            return Ok(false);
+        };
+        if self.scopes[index].vars[key].attrs.readonly {
+            return Err(TypeCmdError::ReadOnly(key.to_string()));
+        }
+        self.scopes[index].vars.remove(key);
+        self.revision += 1;
+        Ok(true)
     }
-    
-    /// Delete a variable
-    pub fn delete(&mut self, key: &str) -> bool {
-        self.store.remove(key).is_some()
+
+    /// Clear all variables in every scope except readonly ones. Returns the
+    /// number of removed variables.
+    pub fn clear(&mut self) -> usize {
+        let mut removed = 0;
+        for scope in &mut self.scopes {
+            let before = scope.vars.len();
+            scope.vars.retain(|_, entry| entry.attrs.readonly);
+            removed += before - scope.vars.len();
+        }
+        self.revision += 1;
+        removed
+    }
+
+    /// Enter a new innermost scope
+    pub fn push_scope(&mut self, kind: ScopeKind) {
+        self.scopes.push(Scope::new(kind));
     }
-    
-    /// Clear all variables
-    pub fn clear(&mut self) {
-        self.store.clear();
+
+    /// Leave the innermost scope, dropping its variables. The global scope
+    /// is never popped; `None` is returned instead.
+    pub fn pop_scope(&mut self) -> Option<Scope> {
+        if self.scopes.len() == 1 {
+            return None;
+        }
+        let scope = self.scopes.pop()?;
+        if !scope.is_empty() {
+            self.revision += 1;
+        }
+        Some(scope)
     }
-    
-    /// Get all variables
-    pub fn all(&self) -> &HashMap<String, String> {
-        &self.store
+
+    /// Number of scopes, 1 when only the global scope exists
+    pub fn depth(&self) -> usize {
+        self.scopes.len()
     }
-    
-    /// Get the number of variables
+
+    /// All scopes, outermost first
+    pub fn scopes(&self) -> &[Scope] {
+        &self.scopes
+    }
+
+    fn visible(&self) -> HashMap<&String, &Entry> {
+        self.scopes.iter().flat_map(|scope| scope.vars.iter()).collect()
+    }
+
+    /// Get all visible variables; inner variables hide outer ones
+    pub fn all(&self) -> HashMap<&String, &Value> {
+        self.visible().into_iter().map(|(name, entry)| (name, &entry.value)).collect()
+    }
+
+    /// Get all visible variables sorted by name
+    pub fn sorted(&self) -> Vec<(&String, &Value)> {
+        let mut vars: Vec<_> = self.all().into_iter().collect();
+        vars.sort_by(|a, b| a.0.cmp(b.0));
+        vars
+    }
+
+    /// Visible variables that are not hidden, sorted by name
+    pub fn listed(&self) -> Vec<(&String, &Value, Attributes)> {
+        let mut vars: Vec<_> = self.visible().into_iter()
+            .filter(|(_, entry)| !entry.attrs.hidden)
+            .map(|(name, entry)| (name, &entry.value, entry.attrs))
+            .collect();
+        vars.sort_by(|a, b| a.0.cmp(b.0));
+        vars
+    }
+
+    /// Visible exported variables sorted by name, the environment passed
+    /// to spawned processes
+    pub fn exported(&self) -> Vec<(&String, &Value)> {
+        let mut vars: Vec<_> = self.visible().into_iter()
+            .filter(|(_, entry)| entry.attrs.exported)
+            .map(|(name, entry)| (name, &entry.value))
+            .collect();
+        vars.sort_by(|a, b| a.0.cmp(b.0));
+        vars
+    }
+
+    /// Get the number of visible variables
     pub fn len(&self) -> usize {
-        self.store.len()
+        self.visible().len()
     }
-    
-    /// Check if the store is empty
+
+    /// Check if no variable is defined
     pub fn is_empty(&self) -> bool {
-        self.store.is_empty()
+        self.scopes.iter().all(Scope::is_empty)
+    }
+
+    /// Change counter, incremented whenever a variable is set or removed
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Set every visible variable of `other`, replacing variables with the
+    /// same names. Returns the number of variables set. Nothing is changed
+    /// if any of the variables cannot be set.
+    pub fn merge(&mut self, other: VariableStore) -> Result<usize> {
+        let mut merged = self.clone();
+        let count = other.len();
+        for (name, value) in other.sorted() {
+            merged.set(name.clone(), value.clone())?;
+        }
+        *self = merged;
+        Ok(count)
+    }
+
+    /// Serialize as a JSON object mapping names to values. Attributes are
+    /// not part of the JSON format and are left out.
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self)
+            .map_err(|e| TypeCmdError::Parse(tr!("vars.invalid_json", e)))
+    }
+
+    /// Parse a JSON object mapping names to scalar values. The variables
+    /// have no attributes.
+    pub fn from_json(text: &str) -> Result<Self> {
+        serde_json::from_str(text)
+            .map_err(|e| TypeCmdError::Parse(tr!("vars.invalid_json", e)))
+    }
+
+    /// Save all variables to `path`.
+    ///
+    /// The file has one variable per line as `name<TAB>type<TAB>value`,
+    /// where type is `string`, `int`, `float`, `bool` or `null`. Variables
+    /// with attributes get a fourth column listing them separated by
+    /// commas, e.g. `readonly,exported`; lines without it, as written by
+    /// older versions, have no attributes. Backslash, tab, newline and
+    /// carriage return in names and values are written as `\\`, `\t`, `\n`
+    /// and `\r`. Lines starting with `#` are comments.
+    pub fn save_file(&self, path: &Path) -> Result<()> {
+        let mut file = File::create(path)?;
+        writeln!(file, "# TypeCmd variables")?;
+        let mut vars: Vec<_> = self.visible().into_iter().collect();
+        vars.sort_by(|a, b| a.0.cmp(b.0));
+        for (name, entry) in vars {
+            let value = &entry.value;
+            write!(file, "{}\t{}\t{}", escape(name), value.type_name(), escape(&value.to_string()))?;
+            let attrs = entry.attrs.names();
+            if !attrs.is_empty() {
+                write!(file, "\t{}", attrs.join(","))?;
+            }
+            writeln!(file)?;
+        }
+        Ok(())
+    }
+
+    /// Load variables and their attributes from a file written by
+    /// `save_file`, replacing variables with the same names. Malformed
+    /// lines and lines for variables that cannot be changed are skipped.
+    /// Returns the number of loaded variables and skipped lines.
+    pub fn load_file(&mut self, path: &Path) -> Result<(usize, usize)> {
+        let reader = BufReader::new(File::open(path)?);
+        let mut loaded = 0;
+        let mut skipped = 0;
+
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            match parse_line(&line).map(|(name, value, attrs)| self.define(name, value, attrs)) {
+                Some(Ok(())) => loaded += 1,
+                _ => skipped += 1,
+            }
+        }
+
+        Ok((loaded, skipped))
+    }
+}
+
+/// Serializes the visible variables as a map from name to value, sorted by
+/// name; attributes are not serialized. Deserialized variables are global
+/// and have no attributes.
+impl Serialize for VariableStore {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_map(self.sorted())
+    }
+}
+
+impl<'de> Deserialize<'de> for VariableStore {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let vars: HashMap<String, Value> = HashMap::deserialize(deserializer)?;
+        let mut store = VariableStore::new();
+        store.scopes[0].vars = vars.into_iter().map(|(name, value)| (name, Entry::new(value))).collect();
+        Ok(store)
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '\t' => out.push_str("\\t"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            c => out.push(c),
+        }
     }
-}
\ No newline at end of file
+    out
+}
+
+fn unescape(text: &str) -> Option<String> {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        out.push(match chars.next()? {
+            '\\' => '\\',
+            't' => '\t',
+            'n' => '\n',
+            'r' => '\r',
+            _ => return None,
+        });
+    }
+    Some(out)
+}
+
+/// Parse one `name<TAB>type<TAB>value[<TAB>attributes]` line
+fn parse_line(line: &str) -> Option<(String, Value, Attributes)> {
+    let mut parts = line.splitn(4, '\t');
+    let name = unescape(parts.next()?)?;
+    let kind = parts.next()?;
+    let text = unescape(parts.next()?)?;
+    if name.is_empty() {
+        return None;
+    }
+    let mut attrs = Attributes::default();
+    for flag in parts.next().unwrap_or("").split(',').filter(|flag| !flag.is_empty()) {
+        *attrs.flag_mut(flag)? = true;
+    }
+
+    let value = match kind {
+        "string" => Value::Str(text),
+        "int" => Value::Int(text.parse().ok()?),
+        "float" => Value::Float(text.parse().ok()?),
+        "bool" => Value::Bool(Value::parse_bool(&text)?),
+        "null" => Value::Null,
+        _ => return None,
+    };
+    Some((name, value, attrs))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+
+    use super::*;
+
+    /// A file path unique to this test process
+    fn temp_file(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("typecmd-{}-{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let mut store = VariableStore::new();
+        let values = [
+            ("text", Value::Str("tab\there\nnew line\r\\ back\\slash".to_string())),
+            ("empty", Value::Str(String::new())),
+            ("name\twith tab", Value::Str("x".to_string())),
+            ("count", Value::Int(-42)),
+            ("ratio", Value::Float(0.5)),
+            ("flag", Value::Bool(true)),
+            ("nothing", Value::Null),
+        ];
+        for (name, value) in &values {
+            store.set(name.to_string(), value.clone()).unwrap();
+        }
+        store.set_attributes("count", Attributes { readonly: true, integer: true, ..Attributes::default() }).unwrap();
+        store.set_attributes("flag", Attributes { exported: true, hidden: true, ..Attributes::default() }).unwrap();
+
+        let path = temp_file("round-trip");
+        store.save_file(&path).unwrap();
+        let mut loaded = VariableStore::new();
+        let counts = loaded.load_file(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(counts.unwrap(), (values.len(), 0));
+        for (name, value) in &values {
+            assert_eq!(loaded.get(name), Some(value), "{}", name);
+            assert_eq!(loaded.attributes(name), store.attributes(name), "{}", name);
+        }
+    }
+
+    #[test]
+    fn load_skips_bad_lines_and_reads_old_files() {
+        let path = temp_file("old-format");
+        fs::write(&path, "# comment\nold\tint\t3\nbad line\nnum\tint\tx\nodd\tstring\ta\\q\nflag\tbool\ttrue\tnosuch\n").unwrap();
+        let mut store = VariableStore::new();
+        let counts = store.load_file(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(counts.unwrap(), (1, 4));
+        assert_eq!(store.get("old"), Some(&Value::Int(3)));
+        assert_eq!(store.attributes("old"), Some(Attributes::default()));
+    }
+
+    #[test]
+    fn load_keeps_readonly_variables() {
+        let mut store = VariableStore::new();
+        store.set("k".to_string(), Value::Int(1)).unwrap();
+        store.set_attributes("k", Attributes { readonly: true, ..Attributes::default() }).unwrap();
+
+        let path = temp_file("readonly");
+        fs::write(&path, "k\tint\t1\treadonly\nk\tint\t2\treadonly\n").unwrap();
+        let counts = store.load_file(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(counts.unwrap(), (1, 1));
+        assert_eq!(store.get("k"), Some(&Value::Int(1)));
+    }
+
+    #[test]
+    fn local_variables_shadow_until_their_scope_is_popped() {
+        let mut store = VariableStore::new();
+        store.set("x".to_string(), Value::Int(1)).unwrap();
+        store.push_scope(ScopeKind::Session);
+        store.push_scope(ScopeKind::Script);
+        store.set_local("x".to_string(), Value::Int(2)).unwrap();
+        store.set_local("y".to_string(), Value::Int(3)).unwrap();
+        assert_eq!(store.get("x"), Some(&Value::Int(2)));
+        assert_eq!(store.len(), 2);
+
+        // `set` changes the nearest variable and creates new ones globally
+        store.set("x".to_string(), Value::Int(4)).unwrap();
+        store.set("z".to_string(), Value::Int(5)).unwrap();
+        assert_eq!(store.scopes()[0].len(), 2);
+        assert_eq!(store.depth(), 3);
+
+        let popped = store.pop_scope().unwrap();
+        assert_eq!(popped.kind, ScopeKind::Script);
+        assert_eq!(popped.len(), 2);
+        assert_eq!(store.get("x"), Some(&Value::Int(1)));
+        assert_eq!(store.get("y"), None);
+        assert_eq!(store.get("z"), Some(&Value::Int(5)));
+
+        assert!(store.pop_scope().is_some());
+        assert!(store.pop_scope().is_none());
+        assert_eq!(store.depth(), 1);
+    }
+
+    #[test]
+    fn readonly_variables_reject_changes() {
+        let mut store = VariableStore::new();
+        store.set("k".to_string(), Value::Int(1)).unwrap();
+        store.set_attributes("k", Attributes { readonly: true, ..Attributes::default() }).unwrap();
+        let revision = store.revision();
+
+        assert!(matches!(store.set("k".to_string(), Value::Int(2)), Err(TypeCmdError::ReadOnly(_))));
+        assert!(matches!(store.delete("k"), Err(TypeCmdError::ReadOnly(_))));
+        assert!(matches!(store.set_attributes("k", Attributes::default()), Err(TypeCmdError::ReadOnly(_))));
+        assert!(matches!(
+            store.define("k".to_string(), Value::Int(2), Attributes { readonly: true, ..Attributes::default() }),
+            Err(TypeCmdError::ReadOnly(_))
+        ));
+        assert_eq!(store.clear(), 0);
+        assert_eq!(store.get("k"), Some(&Value::Int(1)));
+
+        store.clear();
+        assert_eq!(store.revision(), revision + 2);
+    }
+
+    #[test]
+    fn integer_variables_convert_values() {
+        let mut store = VariableStore::new();
+        store.set("n".to_string(), Value::Str(" 7 ".to_string())).unwrap();
+        store.set_attributes("n", Attributes { integer: true, ..Attributes::default() }).unwrap();
+        assert_eq!(store.get("n"), Some(&Value::Int(7)));
+
+        store.set("n".to_string(), Value::Str("12".to_string())).unwrap();
+        assert_eq!(store.get("n"), Some(&Value::Int(12)));
+        assert!(matches!(store.set("n".to_string(), Value::Str("abc".to_string())), Err(TypeCmdError::TypeMismatch(_))));
+        assert_eq!(store.get("n"), Some(&Value::Int(12)));
+    }
+}